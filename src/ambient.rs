@@ -0,0 +1,260 @@
+//Background sounds during work: a ticking clock and white, pink or brown noise. Both are
+//generated as rodio sources, so there are no sound files to ship, and the sound player plays them
+//along with the alerts.
+use rodio::Source;
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 44100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum NoiseColor {
+    White,
+    Pink,
+    Brown,
+}
+
+impl std::fmt::Display for NoiseColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoiseColor::White => write!(f, "White noise"),
+            NoiseColor::Pink => write!(f, "Pink noise"),
+            NoiseColor::Brown => write!(f, "Brown noise"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AmbientSound {
+    pub ticking: bool,
+    pub tick_volume: f32,
+    pub noise: Option<NoiseColor>,
+    pub noise_volume: f32,
+    //Fade the sounds out when work stops instead of stopping them at once.
+    pub fade: bool,
+}
+
+impl Default for AmbientSound {
+    fn default() -> Self {
+        AmbientSound {
+            ticking: false,
+            tick_volume: 0.3,
+            noise: None,
+            noise_volume: 0.2,
+            fade: true,
+        }
+    }
+}
+
+impl AmbientSound {
+    pub fn mix(&self, working: bool) -> Mix {
+        Mix {
+            tick: if working && self.ticking {
+                self.tick_volume
+            } else {
+                0.0
+            },
+            noise: if working && self.noise.is_some() {
+                self.noise_volume
+            } else {
+                0.0
+            },
+            color: self.noise,
+            fade: self.fade,
+        }
+    }
+
+    pub fn draw_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.ticking, "Ticking");
+            ui.add_enabled(
+                self.ticking,
+                egui::Slider::new(&mut self.tick_volume, 0.0..=1.0).text("Volume"),
+            );
+        });
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("ambient_noise")
+                .selected_text(self.noise.map_or("No noise".to_owned(), |c| c.to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.noise, None, "No noise");
+                    for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+                        ui.selectable_value(&mut self.noise, Some(color), color.to_string());
+                    }
+                });
+            ui.add_enabled(
+                self.noise.is_some(),
+                egui::Slider::new(&mut self.noise_volume, 0.0..=1.0).text("Volume"),
+            );
+        });
+        ui.checkbox(&mut self.fade, "Fade out when work stops");
+    }
+}
+
+//Volumes the sound player should move the background sounds towards.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Mix {
+    pub tick: f32,
+    pub noise: f32,
+    pub color: Option<NoiseColor>,
+    pub fade: bool,
+}
+
+//A short click every second, alternating between a higher "tick" and a lower "tock".
+#[derive(Default)]
+pub struct Tick {
+    sample: u64,
+}
+
+impl Iterator for Tick {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let rate = u64::from(SAMPLE_RATE);
+        let in_second = self.sample % rate;
+        let frequency = if (self.sample / rate) % 2 == 0 {
+            1500.0
+        } else {
+            1100.0
+        };
+        self.sample += 1;
+        //The click lasts 20 ms and dies away quickly.
+        if in_second > rate / 50 {
+            return Some(0.0);
+        }
+        let t = in_second as f32 / SAMPLE_RATE as f32;
+        Some((t * frequency * std::f32::consts::TAU).sin() * (-t / 0.003).exp() * 0.8)
+    }
+}
+
+impl Source for Tick {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+    fn channels(&self) -> u16 {
+        1
+    }
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+//Random noise, shaped for pink (Paul Kellet's filter) and brown (integrated white noise).
+pub struct Noise {
+    color: NoiseColor,
+    seed: u32,
+    pink: [f32; 7],
+    brown: f32,
+}
+
+impl Noise {
+    pub fn new(color: NoiseColor) -> Noise {
+        Noise {
+            color,
+            seed: 0x2545_f491,
+            pink: [0.0; 7],
+            brown: 0.0,
+        }
+    }
+
+    //xorshift32, plenty random for noise, from -1.0 to 1.0.
+    fn white(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for Noise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let white = self.white();
+        Some(match self.color {
+            NoiseColor::White => white * 0.5,
+            NoiseColor::Pink => {
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                pink * 0.11
+            }
+            NoiseColor::Brown => {
+                self.brown = (self.brown + 0.02 * white) / 1.02;
+                self.brown * 3.5
+            }
+        })
+    }
+}
+
+impl Source for Noise {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+    fn channels(&self) -> u16 {
+        1
+    }
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plays_during_work() {
+        let settings = AmbientSound {
+            ticking: true,
+            noise: Some(NoiseColor::Pink),
+            ..AmbientSound::default()
+        };
+        let mix = settings.mix(true);
+        assert_eq!((mix.tick, mix.noise), (0.3, 0.2));
+        assert_eq!(mix.color, Some(NoiseColor::Pink));
+        let silent = |mix: Mix| mix.tick == 0.0 && mix.noise == 0.0;
+        assert!(silent(settings.mix(false)));
+        assert!(silent(AmbientSound::default().mix(true)));
+    }
+
+    #[test]
+    fn ticks_once_a_second() {
+        let rate = SAMPLE_RATE as usize;
+        let samples: Vec<f32> = Tick::default().take(2 * rate).collect();
+        let loud =
+            |range: std::ops::Range<usize>| samples[range].iter().any(|sample| sample.abs() > 0.1);
+        assert!(loud(0..rate / 100));
+        assert!(!loud(rate / 10..rate));
+        assert!(loud(rate..rate + rate / 100));
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+    }
+
+    #[test]
+    fn noise_stays_in_range() {
+        for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+            let samples: Vec<f32> = Noise::new(color).take(SAMPLE_RATE as usize).collect();
+            assert!(
+                samples.iter().all(|sample| sample.abs() <= 1.0),
+                "{}",
+                color
+            );
+            assert!(
+                samples.iter().any(|sample| sample.abs() > 0.01),
+                "{}",
+                color
+            );
+        }
+    }
+}
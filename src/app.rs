@@ -1,12 +1,43 @@
+use crate::ambient::AmbientSound;
+use crate::break_overlay::{BreakOverlay, EscapeHold, ESCAPE_HOLD_SECONDS};
+use crate::calendar::{Calendar, Meeting};
+use crate::clock::{self, Date, DAY_NAMES};
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use crate::control::{ControlCommand, ControlSocket};
 use crate::custom_widgets::TimerDisplay;
-use crate::timer::{TimerData, TimerState, WorkTimes};
+use crate::export::ExportDialog;
+use crate::goals::{DailyGoal, GoalUnit, ProgressCache};
+use crate::idle::IdleDetector;
+use crate::import::ImportDialog;
+use crate::planner::{self, BlockKind, PlanStart, PlannedBlock};
+use crate::report::{self, WeeklyReport};
+use crate::schedule::WorkingHours;
+use crate::session::{
+    IdleGap, Interruption, InterruptionKind, Reflection, SessionHistory, StrictOverride,
+};
+use crate::sound::SoundPlayer;
+use crate::strict::{OverrideDialog, StrictAction, StrictMode};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::task_files::{self, TaskFileWatcher};
+use crate::tasks::{self, SessionFilter, TaskEdits, TaskList};
+use crate::timer::{FlowtimeBreaks, PhaseWarning, TimerData, TimerEvent, TimerState, WorkTimes};
 use crate::visuals::TimerAppVisuals;
 use crate::AppColorScheme;
 use eframe::egui::RichText;
-use egui::{Button, TextStyle, Ui};
+#[cfg(not(target_arch = "wasm32"))]
+use egui::Sense;
+use egui::{Button, Color32, Pos2, TextStyle, Ui, Vec2};
 use std::collections::HashMap;
 use std::time::Duration;
-use std::time::Instant;
+
+//Width of the skip button next to the timer and the timer display's height relative to its width.
+const SKIP_BUTTON_WIDTH: f32 = 10.0;
+const TIMER_ASPECT_RATIO: f32 = 0.3;
+//Size of the borderless mini window, in the same units as `NativeOptions::initial_window_size`.
+#[cfg(not(target_arch = "wasm32"))]
+const MINI_WINDOW_SIZE: Vec2 = Vec2::new(300.0, 90.0);
+//How long the timer flashes for the warning before a phase ends.
+const WARNING_FLASH_SECONDS: f64 = 5.0;
 
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
@@ -19,19 +50,147 @@ pub struct TimerApp {
     current_screen: Screen,
     #[serde(skip)]
     timer_visuals: TimerAppVisuals,
+    mini_window_pos: Option<Pos2>,
+    //Position and size of the normal window while the mini window is shown.
+    #[serde(skip)]
+    normal_window: Option<(Option<Pos2>, Vec2)>,
+    //Last title given to the window/browser tab, so it is only set when it changes.
+    #[serde(skip)]
+    window_title: String,
+    history: SessionHistory,
+    #[serde(skip)]
+    goal_progress: ProgressCache,
+    tasks: TaskList,
+    //Counts up with every break, so the suggested activities take turns.
+    activity_turn: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    task_file_watcher: TaskFileWatcher,
+    #[serde(skip)]
+    idle_detector: IdleDetector,
+    //Time and note of an interruption whose kind has not been picked yet.
+    #[serde(skip)]
+    pending_interruption: Option<(u64, String)>,
+    //Start of the work session being reflected on and the answers so far.
+    #[serde(skip)]
+    pending_reflection: Option<(u64, Reflection)>,
+    //When working hours were last checked for a block starting, 0 before the first check.
+    #[serde(skip)]
+    schedule_checked: u64,
+    //Start of a working hours block that began with no timer running.
+    #[serde(skip)]
+    schedule_reminder: Option<u64>,
+    //Confirmation asked for before strict mode lets the user interrupt a work phase.
+    #[serde(skip)]
+    override_dialog: Option<OverrideDialog>,
+    //Esc held down on the break overlay.
+    #[serde(skip)]
+    escape_hold: EscapeHold,
+    //Input time until which the timer flashes to warn that the phase is about to end.
+    #[serde(skip)]
+    flash_until: f64,
+    //Alerts and background sounds.
+    #[serde(skip)]
+    sound: SoundPlayer,
+    #[serde(skip)]
+    calendar: Calendar,
+    //Meetings of the next day, worked out from the calendar every few minutes.
+    #[serde(skip)]
+    meetings: Vec<Meeting>,
+    #[serde(skip)]
+    meetings_updated: u64,
+    #[serde(skip)]
+    calendar_error: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    calendar_watcher: TaskFileWatcher,
+    //Commands sent by other programs, None if another instance already listens.
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    #[serde(skip)]
+    control_socket: Option<ControlSocket>,
 }
 
 #[derive(PartialEq, Eq, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Screen {
     TimerScreen,
-    SettingsScreen { editable_settings: Vec<String> },
+    SettingsScreen {
+        editable_settings: Box<EditableSettings>,
+    },
+    FocusScreen,
+    //Covers the screen during a break, `previous` is shown again afterwards.
+    BreakScreen {
+        previous: Box<Screen>,
+        since: u64,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    MiniScreen,
+    HistoryScreen {
+        export_dialog: Option<Box<ExportDialog>>,
+        import_dialog: Option<Box<ImportDialog>>,
+        search: String,
+        filter: SessionFilter,
+    },
+    StatsScreen {
+        week_start: Date,
+        status: Option<String>,
+        filter: SessionFilter,
+    },
+    TasksScreen {
+        new_task: String,
+        new_project: String,
+        import_path: String,
+        status: Option<String>,
+    },
+    PlannerScreen,
+}
+
+//The settings that are typed in as text on the settings screen, they are only checked and applied
+//when the screen is left.
+#[derive(PartialEq, Eq, Clone)]
+pub struct EditableSettings {
+    pub work: String,
+    pub long_break: String,
+    pub short_break: String,
+    pub flowtime_table: String,
+    pub pause_reasons: String,
+    //Indexed by Date::weekday.
+    pub working_hours: [String; 7],
+    pub short_break_activities: String,
+    pub long_break_activities: String,
 }
 
-#[derive(PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct Setting {
     work_times_settings: HashMap<WorkTimes, Duration>,
     alert_sound_path: String,
+    ui_scale: f32,
+    auto_pause_on_idle: bool,
+    idle_threshold: Duration,
+    use_system_idle: bool,
+    //Count up past the end of a phase instead of going straight to the next one.
+    overtime: bool,
+    //Every how many work phases the break is a long one, 0 for only short breaks as before.
+    long_break_every: u32,
+    //Open-ended work phases with breaks that depend on how long the work lasted.
+    flowtime: bool,
+    flowtime_breaks: FlowtimeBreaks,
+    phase_warning: PhaseWarning,
+    ambient_sound: AmbientSound,
+    //Reasons to pick from when pausing.
+    pause_reasons: Vec<String>,
+    //Things to do during breaks, suggested in turn.
+    short_break_activities: Vec<String>,
+    long_break_activities: Vec<String>,
+    //Ask what was accomplished after each completed work session.
+    reflection_prompt: bool,
+    working_hours: WorkingHours,
+    //iCalendar file with meetings the planner and the timer look out for.
+    calendar_path: String,
+    strict_mode: StrictMode,
+    break_overlay: BreakOverlay,
+    daily_goal: DailyGoal,
 }
 
 impl Default for Setting {
@@ -43,6 +202,31 @@ impl Default for Setting {
                 (WorkTimes::Long, Duration::from_secs(15 * 60)),
             ]),
             alert_sound_path: "assets/alert_sound.wav".into(),
+            ui_scale: 2.5,
+            auto_pause_on_idle: false,
+            idle_threshold: Duration::from_secs(5 * 60),
+            use_system_idle: false,
+            overtime: false,
+            long_break_every: 0,
+            flowtime: false,
+            flowtime_breaks: FlowtimeBreaks::default(),
+            phase_warning: PhaseWarning::default(),
+            ambient_sound: AmbientSound::default(),
+            pause_reasons: ["Break", "Meeting", "Call", "Distraction"]
+                .map(String::from)
+                .to_vec(),
+            short_break_activities: ["Stretch", "Drink some water", "Look 20 ft away for 20 s"]
+                .map(String::from)
+                .to_vec(),
+            long_break_activities: ["Go for a walk", "Get a snack", "Tidy up your desk"]
+                .map(String::from)
+                .to_vec(),
+            reflection_prompt: false,
+            working_hours: WorkingHours::default(),
+            calendar_path: String::new(),
+            strict_mode: StrictMode::default(),
+            break_overlay: BreakOverlay::default(),
+            daily_goal: DailyGoal::default(),
         }
     }
 }
@@ -54,6 +238,46 @@ impl Setting {
     pub fn alert_sound_setting(&self) -> &String {
         &self.alert_sound_path
     }
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+    pub fn working_hours(&self) -> &WorkingHours {
+        &self.working_hours
+    }
+    pub fn overtime(&self) -> bool {
+        self.overtime
+    }
+    pub fn long_break_every(&self) -> u32 {
+        self.long_break_every
+    }
+    pub fn flowtime(&self) -> bool {
+        self.flowtime
+    }
+    pub fn flowtime_breaks(&self) -> &FlowtimeBreaks {
+        &self.flowtime_breaks
+    }
+    pub fn phase_warning(&self) -> &PhaseWarning {
+        &self.phase_warning
+    }
+    pub fn pause_reasons(&self) -> &Vec<String> {
+        &self.pause_reasons
+    }
+    //Activities suggested during breaks of that kind, none during work.
+    pub fn break_activities(&self, work_time: WorkTimes) -> &[String] {
+        match work_time {
+            WorkTimes::Work => &[],
+            WorkTimes::Short => &self.short_break_activities,
+            WorkTimes::Long => &self.long_break_activities,
+        }
+    }
+}
+
+//"Stretch, Drink some water" -> ["Stretch", "Drink some water"]
+fn parse_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty())
+        .collect()
 }
 
 impl Default for TimerApp {
@@ -64,6 +288,32 @@ impl Default for TimerApp {
             color_scheme: AppColorScheme::default(),
             current_screen: Screen::TimerScreen,
             timer_visuals: TimerAppVisuals::default(),
+            mini_window_pos: None,
+            normal_window: None,
+            window_title: String::new(),
+            history: SessionHistory::default(),
+            goal_progress: ProgressCache::default(),
+            tasks: TaskList::default(),
+            activity_turn: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            task_file_watcher: TaskFileWatcher::default(),
+            idle_detector: IdleDetector::default(),
+            pending_interruption: None,
+            pending_reflection: None,
+            schedule_checked: 0,
+            schedule_reminder: None,
+            override_dialog: None,
+            escape_hold: EscapeHold::default(),
+            flash_until: 0.0,
+            sound: SoundPlayer::default(),
+            calendar: Calendar::default(),
+            meetings: Vec::new(),
+            meetings_updated: 0,
+            calendar_error: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            calendar_watcher: TaskFileWatcher::default(),
+            #[cfg(all(unix, not(target_arch = "wasm32")))]
+            control_socket: None,
         }
     }
 }
@@ -82,64 +332,235 @@ impl TimerApp {
             app.timer_data = timer_data;
             app
         };
-        app.timer_visuals.setup_app_visuals(cc);
+        app.timer_visuals.setup_app_visuals(
+            cc,
+            &app.color_scheme,
+            app.timer_data.work_time(),
+            app.settings.ui_scale,
+        );
+        #[cfg(all(unix, not(target_arch = "wasm32")))]
+        {
+            app.control_socket = ControlSocket::listen(cc.egui_ctx.clone());
+        }
         app
     }
 
-    fn draw_timer_text_element<'a>(&mut self, ui: &'a mut Ui) {
+    fn draw_timer_text_element<'a>(&mut self, ui: &'a mut Ui, size: Vec2) {
+        let colors = self.timer_visuals.current_colors();
         let timer_bg_color = match self.timer_data.timer_state() {
-            TimerState::Started(_) => self.color_scheme.timer_active,
-            _ => self.color_scheme.timer_paused,
+            TimerState::Started(_) | TimerState::Flowing(_) => colors.timer_active,
+            TimerState::Overtime(_) => colors.timer_overtime,
+            _ => colors.timer_paused,
         };
         let display_string = self.timer_data.calculate_timer_text(&self.settings);
-        ui.add(TimerDisplay::new(
-            timer_bg_color,
-            self.color_scheme.ligth_bg_stroke,
-            display_string,
-        ));
+        let mut display =
+            TimerDisplay::new(timer_bg_color, colors.ligth_bg_stroke, display_string, size);
+        if ui.input(|i| i.time) < self.flash_until
+            && matches!(self.timer_data.timer_state(), TimerState::Started(_))
+        {
+            display = display.flashing(colors.timer_overtime);
+        }
+        ui.add(display);
     }
 
-    fn draw_skip_button_element<'a>(&mut self, ui: &'a mut Ui) {
-        if ui.add_sized([10.0, 30.0], Button::new(">")).clicked() {
-            match self.timer_data.timer_state() {
-                TimerState::Done => (),
-                TimerState::Paused(_) | TimerState::Started(_) => {
-                    *self.timer_data.timer_state_mut() = TimerState::Done
-                }
+    fn draw_skip_button_element<'a>(&mut self, ui: &'a mut Ui, height: f32) {
+        if ui
+            .add_sized([SKIP_BUTTON_WIDTH, height], Button::new(">"))
+            .clicked()
+        {
+            self.request(StrictAction::Skip);
+        }
+    }
+
+    //Whether strict mode has to be overridden before doing this to the current phase. Breaks,
+    //starting and resuming, and ending open-ended Flowtime work are never held back. An override
+    //is for one action only, the next pause or skip has to be confirmed again.
+    fn needs_override(&self, action: StrictAction) -> bool {
+        let running = match self.timer_data.timer_state() {
+            TimerState::Started(_) | TimerState::Flowing(_) => true,
+            TimerState::Paused(_) => action != StrictAction::Pause,
+            _ => false,
+        };
+        self.settings.strict_mode.enabled
+            && running
+            && *self.timer_data.work_time() == WorkTimes::Work
+            && !(action == StrictAction::Skip && self.timer_data.is_open_ended())
+    }
+
+    //Does what the user asked for, or asks to confirm it first in strict mode.
+    fn request(&mut self, action: StrictAction) {
+        if self.needs_override(action) {
+            let session = self
+                .timer_data
+                .active_session()
+                .map(|session| session.start);
+            self.override_dialog = Some(OverrideDialog::new(action, session));
+        } else {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: StrictAction) {
+        match action {
+            StrictAction::Pause => self.timer_data.toggle_pause(&self.settings),
+            StrictAction::Skip if self.timer_data.is_open_ended() => {
+                self.timer_data.end_flow(&self.settings)
             }
+            StrictAction::Skip => self.timer_data.stop(),
+            StrictAction::SwitchTo(work_time) => self.timer_data.set_work_time(work_time),
         }
     }
 
-    fn draw_pause_button_element<'a>(&mut self, ui: &'a mut Ui) {
+    fn draw_override_dialog(&mut self, ctx: &egui::Context) {
+        let dialog = match &mut self.override_dialog {
+            Some(dialog) => dialog,
+            None => return,
+        };
+        match dialog.show(ctx, &self.settings.strict_mode) {
+            Some(true) => self.confirm_override(),
+            Some(false) => self.override_dialog = None,
+            None => {}
+        }
+    }
+
+    //Does what strict mode was overridden for. If the work phase ended or the timer was paused
+    //in the meantime the action would no longer mean the same, so it is dropped and the dialog
+    //says so.
+    fn confirm_override(&mut self) {
+        let (action, session) = match &self.override_dialog {
+            Some(dialog) => (dialog.action, dialog.session),
+            None => return,
+        };
+        let current = self
+            .timer_data
+            .active_session()
+            .map(|session| session.start);
+        if current != session || !self.needs_override(action) {
+            if let Some(dialog) = &mut self.override_dialog {
+                dialog.phase_changed();
+            }
+            return;
+        }
+        self.override_dialog = None;
+        self.timer_data.log_override(StrictOverride {
+            time: clock::now_unix(),
+            action: action.to_string(),
+        });
+        self.perform(action);
+    }
+
+    fn draw_pause_button_element<'a>(&mut self, ui: &'a mut Ui, width: f32) {
         let button_string = match self.timer_data.timer_state() {
             TimerState::Paused(_) => "Restart timer",
             TimerState::Done => "Start Timer",
-            TimerState::Started(_) => "Pause",
+            TimerState::Started(_) | TimerState::Flowing(_) => "Pause",
+            TimerState::Overtime(_) => "Next phase",
         };
         if ui
-            .add_sized([80.0, 10.0], egui::Button::new(button_string))
+            .add_sized([width, 10.0], egui::Button::new(button_string))
             .clicked()
         {
-            match self.timer_data.timer_state() {
-                TimerState::Done => {
-                    *self.timer_data.timer_state_mut() = TimerState::Started(Instant::now())
+            self.request(StrictAction::Pause);
+        }
+    }
+    //Lets the user say why the timer is paused.
+    fn draw_pause_reason_element(&mut self, ui: &mut Ui) {
+        if !matches!(self.timer_data.timer_state(), TimerState::Paused(_))
+            || self.timer_data.active_session().is_none()
+        {
+            return;
+        }
+        let current = self.timer_data.pause_reason().map(String::from);
+        let mut selected = current.clone();
+        egui::ComboBox::from_label("Pause reason")
+            .selected_text(selected.as_deref().unwrap_or("None"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "None");
+                for reason in &self.settings.pause_reasons {
+                    ui.selectable_value(&mut selected, Some(reason.clone()), reason);
                 }
-                TimerState::Started(started_time) => {
-                    *self.timer_data.timer_state_mut() = TimerState::Paused(started_time.elapsed())
+            });
+        if selected != current {
+            self.timer_data.set_pause_reason(selected);
+        }
+    }
+    //The activity suggested for the break, which can be swapped for the next one or marked done.
+    fn draw_break_activity_element(&mut self, ui: &mut Ui) {
+        let work_time = *self.timer_data.work_time();
+        let activities = self.settings.break_activities(work_time);
+        if activities.is_empty() {
+            return;
+        }
+        let activity = activities[self.activity_turn % activities.len()].clone();
+        let session = self.timer_data.active_session();
+        let running = session.is_some();
+        let mut done = session.map_or(false, |session| session.activity.is_some());
+        ui.horizontal(|ui| {
+            ui.label(format!("Try: {}", activity));
+            if ui.add_enabled(!done, Button::new("Another")).clicked() {
+                self.activity_turn += 1;
+            }
+            if ui
+                .add_enabled(running, egui::Checkbox::new(&mut done, "Done"))
+                .changed()
+            {
+                self.timer_data
+                    .set_break_activity(Some(activity).filter(|_| done));
+            }
+        });
+    }
+    //"+1 min" and "+5 min" to give a running or paused phase a little more time.
+    fn draw_extend_buttons_element(&mut self, ui: &mut Ui) {
+        let running = *self.timer_data.timer_state() != TimerState::Done;
+        ui.horizontal(|ui| {
+            if self.timer_data.is_open_ended() {
+                if ui
+                    .add_enabled(running, Button::new("Take a break"))
+                    .clicked()
+                {
+                    self.timer_data.end_flow(&self.settings);
                 }
-                TimerState::Paused(paused_time) => {
-                    *self.timer_data.timer_state_mut() =
-                        TimerState::Started(Instant::now() - *paused_time)
+                return;
+            }
+            for minutes in [1, 5] {
+                if ui
+                    .add_enabled(running, Button::new(format!("+{} min", minutes)))
+                    .clicked()
+                {
+                    self.timer_data.extend(Duration::from_secs(minutes * 60));
                 }
             }
+        });
+    }
+    //Carries out the commands that came in over the control socket since the last frame.
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    fn handle_control_commands(&mut self) {
+        while let Some((command, reply)) = self.control_socket.as_ref().and_then(|s| s.try_recv()) {
+            let result = match command {
+                ControlCommand::Extend(minutes) => {
+                    if *self.timer_data.timer_state() == TimerState::Done {
+                        Err(String::from("No phase is running"))
+                    } else if self.timer_data.is_open_ended() {
+                        Err(String::from("A flowtime phase has no end to extend"))
+                    } else {
+                        self.timer_data.extend(Duration::from_secs(minutes * 60));
+                        Ok(format!("Extended by {} min", minutes))
+                    }
+                }
+            };
+            let _ = reply.send(result);
         }
     }
     fn draw_set_time_buttons_element<'a>(&mut self, ui: &'a mut Ui) {
         ui.vertical(|ui| {
             let changing_time_allowed = match self.timer_data.timer_state() {
-                TimerState::Started(_) => false,
+                TimerState::Started(_) | TimerState::Flowing(_) => false,
                 _ => true,
             };
+            ui.add_enabled(
+                *self.timer_data.timer_state() == TimerState::Done,
+                egui::Checkbox::new(&mut self.settings.flowtime, "Flowtime"),
+            );
             if ui
                 .add_enabled(
                     changing_time_allowed,
@@ -150,8 +571,7 @@ impl TimerApp {
                 )
                 .clicked()
             {
-                *self.timer_data.work_time_mut() = WorkTimes::Work;
-                *self.timer_data.timer_state_mut() = TimerState::Done;
+                self.request(StrictAction::SwitchTo(WorkTimes::Work));
             }
             if ui
                 .add_enabled(
@@ -163,8 +583,7 @@ impl TimerApp {
                 )
                 .clicked()
             {
-                *self.timer_data.work_time_mut() = WorkTimes::Short;
-                *self.timer_data.timer_state_mut() = TimerState::Done;
+                self.request(StrictAction::SwitchTo(WorkTimes::Short));
             }
             if ui
                 .add_enabled(
@@ -173,31 +592,1185 @@ impl TimerApp {
                 )
                 .clicked()
             {
-                *self.timer_data.work_time_mut() = WorkTimes::Long;
-                *self.timer_data.timer_state_mut() = TimerState::Done;
+                self.request(StrictAction::SwitchTo(WorkTimes::Long));
             }
         });
     }
 
+    //Title such as "12:34 – Work (paused)" so the countdown is visible from the taskbar.
+    fn timer_title(&self) -> String {
+        let timer_text = self.timer_data.calculate_timer_text(&self.settings);
+        let title = format!("{} – {}", timer_text.trim(), self.timer_data.work_time());
+        match self.timer_data.timer_state() {
+            TimerState::Started(_) | TimerState::Flowing(_) => title,
+            TimerState::Paused(_) => format!("{} (paused)", title),
+            TimerState::Overtime(_) => format!("{} (overtime)", title),
+            TimerState::Done => String::from("Pomodoro Timer"),
+        }
+    }
+
+    fn update_window_title(&mut self, _frame: &mut eframe::Frame) {
+        let title = self.timer_title();
+        if title == self.window_title {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        _frame.set_window_title(&title);
+        #[cfg(target_arch = "wasm32")]
+        if let Some(document) = eframe::web_sys::window().and_then(|window| window.document()) {
+            document.set_title(&title);
+        }
+        self.window_title = title;
+    }
+
     pub fn draw_timer_screen<'a>(&mut self, ui: &'a mut Ui) {
+        //The timer grows with the window, but never gets smaller than its original size.
+        let timer_width =
+            (ui.available_width() - SKIP_BUTTON_WIDTH - ui.spacing().item_spacing.x).max(100.0);
+        let timer_size = Vec2::new(timer_width, timer_width * TIMER_ASPECT_RATIO);
         ui.horizontal(|ui| {
-            self.draw_timer_text_element(ui);
-            self.draw_skip_button_element(ui);
+            self.draw_timer_text_element(ui, timer_size);
+            self.draw_skip_button_element(ui, timer_size.y);
         });
-        self.draw_pause_button_element(ui);
+        self.draw_pause_button_element(ui, timer_size.x);
+        self.draw_extend_buttons_element(ui);
+        self.draw_pause_reason_element(ui);
+        self.draw_break_activity_element(ui);
+        if ui
+            .add(
+                egui::TextEdit::singleline(self.timer_data.task_mut())
+                    .hint_text("Task")
+                    .desired_width(timer_size.x),
+            )
+            .changed()
+        {
+            self.timer_data.clear_tags();
+        }
+        self.draw_task_picker_element(ui);
+        self.draw_interruption_element(ui);
         self.draw_set_time_buttons_element(ui);
+        self.draw_goal_progress_element(ui);
+        self.draw_finish_time_element(ui);
+        self.draw_meeting_element(ui);
+    }
+
+    //Picks the next session's task from the task list, or just its project.
+    fn draw_task_picker_element(&mut self, ui: &mut Ui) {
+        let project_names: Vec<String> = self
+            .tasks
+            .projects()
+            .iter()
+            .map(|project| project.name.clone())
+            .collect();
+        ui.horizontal(|ui| {
+            let mut picked = None;
+            egui::ComboBox::from_id_source("task_picker")
+                .selected_text("Tasks")
+                .show_ui(ui, |ui| {
+                    for task in self.tasks.open_tasks() {
+                        if ui.selectable_label(false, &task.name).clicked() {
+                            picked = Some(task.clone());
+                        }
+                    }
+                });
+            if let Some(task) = picked {
+                self.timer_data.select_task(&task);
+            }
+            if let Some(color) = self
+                .timer_data
+                .project_mut()
+                .as_deref()
+                .and_then(|project| self.tasks.project_color(project))
+            {
+                ui.colored_label(color, "●");
+            }
+            tasks::project_combo_box(
+                ui,
+                "timer_project",
+                self.timer_data.project_mut(),
+                &project_names,
+                "No project",
+            );
+            let tags: Vec<String> = self
+                .timer_data
+                .tags()
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect();
+            ui.label(tags.join(" "));
+        });
+    }
+
+    //Button (or the I key) to log an interruption, with the tally of the current session.
+    fn draw_interruption_element(&mut self, ui: &mut Ui) {
+        let tally = match self.timer_data.active_session() {
+            Some(session) if session.work_time == WorkTimes::Work => session.interruption_tally(),
+            _ => return,
+        };
+        let hotkey =
+            ui.memory(|m| m.focus().is_none()) && ui.input(|i| i.key_pressed(egui::Key::I));
+        ui.horizontal(|ui| {
+            if ui.button("Interrupted").clicked() || hotkey {
+                self.pending_interruption
+                    .get_or_insert((clock::now_unix(), String::new()));
+            }
+            ui.label(tally);
+        });
+    }
+
+    //Short prompt after a completed work session, the answers are stored with the session.
+    fn draw_reflection_dialog(&mut self, ctx: &egui::Context) {
+        let (start, reflection) = match &mut self.pending_reflection {
+            Some(pending) => pending,
+            None => return,
+        };
+        let mut save = false;
+        let mut skip = false;
+        egui::Window::new("How did it go?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut reflection.accomplished)
+                        .hint_text("What did you accomplish?")
+                        .desired_rows(2),
+                );
+                ui.add(egui::Slider::new(&mut reflection.focus, 1..=5).text("Focus"));
+                ui.add(egui::Slider::new(&mut reflection.energy, 1..=5).text("Energy"));
+                ui.horizontal(|ui| {
+                    save = ui.button("Save").clicked();
+                    skip = ui.button("Skip").clicked();
+                });
+            });
+        if save {
+            let mut reflection = reflection.clone();
+            reflection.accomplished = reflection.accomplished.trim().to_owned();
+            self.history.set_reflection(*start, reflection);
+            self.pending_reflection = None;
+        } else if skip {
+            self.pending_reflection = None;
+        }
+    }
+
+    fn draw_interruption_dialog(&mut self, ctx: &egui::Context) {
+        let (time, note) = match &mut self.pending_interruption {
+            Some(pending) => pending,
+            None => return,
+        };
+        let mut kind = None;
+        let mut cancel = false;
+        egui::Window::new("Interruption")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(note).hint_text("Note (optional)"));
+                ui.horizontal(|ui| {
+                    if ui.button("Internal").clicked() {
+                        kind = Some(InterruptionKind::Internal);
+                    }
+                    if ui.button("External").clicked() {
+                        kind = Some(InterruptionKind::External);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if let Some(kind) = kind {
+            let note = Some(note.trim().to_owned()).filter(|note| !note.is_empty());
+            self.timer_data.log_interruption(Interruption {
+                time: *time,
+                kind,
+                note,
+            });
+            self.pending_interruption = None;
+        } else if cancel {
+            self.pending_interruption = None;
+        }
+    }
+
+    //"5/8 today" and the current streak, hidden when no daily goal is set.
+    fn draw_goal_progress_element(&mut self, ui: &mut Ui) {
+        let goal = &self.settings.daily_goal;
+        if !goal.is_enabled() {
+            return;
+        }
+        let progress = self
+            .goal_progress
+            .progress(goal, &self.history, clock::now_unix());
+        let unit = match goal.unit {
+            GoalUnit::Pomodoros => "",
+            GoalUnit::FocusMinutes => " min",
+        };
+        let mut text = format!("{}/{}{} today", progress.today, progress.target, unit);
+        if progress.streak > 0 {
+            text.push_str(&format!(", {} day streak", progress.streak));
+        }
+        ui.label(RichText::new(text).text_style(TextStyle::Name("Small Text".into())));
+    }
+
+    //"Done at 17:40" for the pomodoros still estimated on open tasks, hidden when none are left.
+    fn draw_finish_time_element(&self, ui: &mut Ui) {
+        if let Some(finish) = planner::finish_time(&self.plan_day()) {
+            ui.label(
+                RichText::new(format!("Done at {}", planner::format_clock_time(finish)))
+                    .text_style(TextStyle::Name("Small Text".into())),
+            );
+        }
+    }
+
+    //The next meeting, and a warning with an offer to end early when work would run into it.
+    fn draw_meeting_element(&mut self, ui: &mut Ui) {
+        let now = clock::now_unix();
+        let meeting = match self.meetings.iter().find(|meeting| meeting.end > now) {
+            Some(meeting) => meeting.clone(),
+            None => return,
+        };
+        let small =
+            |text: String| RichText::new(text).text_style(TextStyle::Name("Small Text".into()));
+        if meeting.start <= now {
+            ui.label(small(format!(
+                "In {} until {}",
+                meeting.title,
+                planner::format_clock_time(meeting.end)
+            )));
+            return;
+        }
+        ui.label(small(format!(
+            "Next: {} at {}",
+            meeting.title,
+            planner::format_clock_time(meeting.start)
+        )));
+        if *self.timer_data.work_time() != WorkTimes::Work || self.timer_data.is_open_ended() {
+            return;
+        }
+        let state = *self.timer_data.timer_state();
+        let left = match state {
+            TimerState::Started(_) | TimerState::Paused(_) => self.timer_data.remaining(),
+            TimerState::Done => self
+                .settings
+                .work_time_settings()
+                .get(&WorkTimes::Work)
+                .copied(),
+            _ => None,
+        };
+        let until_meeting = Duration::from_secs(meeting.start - now);
+        if left.map_or(true, |left| left <= until_meeting) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                small("This work phase runs into it".to_owned()),
+            );
+            if until_meeting < Duration::from_secs(60) {
+                return;
+            }
+            let label = if state == TimerState::Done {
+                format!("Start {} min", until_meeting.as_secs() / 60)
+            } else {
+                format!("End at {}", planner::format_clock_time(meeting.start))
+            };
+            if ui.small_button(label).clicked() {
+                if state == TimerState::Done {
+                    self.timer_data.toggle_pause(&self.settings);
+                }
+                self.timer_data.shorten_to(until_meeting);
+            }
+        });
+    }
+
+    //Reads the calendar file again when it changed and keeps the next day's meetings at hand.
+    fn update_meetings(&mut self, ctx: &egui::Context) {
+        let now = clock::now_unix();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = self.settings.calendar_path.trim().to_owned();
+            if path.is_empty() {
+                if self.calendar != Calendar::default() {
+                    self.calendar = Calendar::default();
+                    self.meetings_updated = 0;
+                }
+                self.calendar_error = None;
+            } else if !self
+                .calendar_watcher
+                .changed_files(&[path.clone()], ctx.input(|i| i.time))
+                .is_empty()
+            {
+                match crate::calendar::read_calendar(&path) {
+                    Ok(calendar) => {
+                        self.calendar = calendar;
+                        self.calendar_error = None;
+                    }
+                    Err(err) => self.calendar_error = Some(err),
+                }
+                self.meetings_updated = 0;
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = ctx;
+        if now.saturating_sub(self.meetings_updated) >= 5 * 60 {
+            self.meetings = self.calendar.meetings_between(now, now + 24 * 3600);
+            self.meetings_updated = now;
+        }
+    }
+
+    //The rest of the day as the timer would run it, from the current phase on.
+    fn plan_day(&self) -> Vec<PlannedBlock> {
+        let task = self.timer_data.task().trim();
+        let start = PlanStart {
+            now: clock::now_unix(),
+            work_time: *self.timer_data.work_time(),
+            remaining: self.timer_data.remaining(),
+            task: Some(task.to_owned()).filter(|task| !task.is_empty()),
+            work_since_long_break: self.timer_data.work_since_long_break(),
+        };
+        planner::plan_day(
+            &start,
+            &self.tasks.open_tasks(),
+            self.settings.work_time_settings(),
+            self.settings.long_break_every(),
+            &self.meetings,
+        )
+    }
+
+    //Timeline of the planned work phases, breaks and meetings, work in the color of the task's
+    //project.
+    pub fn draw_planner_screen(&mut self, ui: &mut Ui) {
+        let blocks = self.plan_day();
+        if let Some(finish) = planner::finish_time(&blocks) {
+            let work = blocks.iter().filter(|block| block.is_work()).count();
+            ui.label(format!(
+                "{} pomodoros left, done at {}",
+                work,
+                planner::format_clock_time(finish)
+            ));
+        }
+        let project_colors: HashMap<&str, Color32> = self
+            .tasks
+            .tasks()
+            .iter()
+            .filter_map(|task| {
+                let project = task.project.as_deref()?;
+                Some((task.name.as_str(), self.tasks.project_color(project)?))
+            })
+            .collect();
+        let phase_colors = &self.color_scheme.phase_colors;
+        planner::draw_timeline(ui, &blocks, |block| match block.kind {
+            BlockKind::Phase(work_time) => block
+                .label
+                .as_deref()
+                .and_then(|task| project_colors.get(task).copied())
+                .or_else(|| {
+                    phase_colors
+                        .get(&work_time)
+                        .map(|colors| colors.timer_active)
+                })
+                .unwrap_or(Color32::GRAY),
+            BlockKind::Meeting => Color32::GRAY,
+        });
+    }
+
+    //Full screen view with only the countdown and the pause button, returns true when the user
+    //wants to leave it.
+    pub fn draw_focus_screen(&mut self, ui: &mut Ui) -> bool {
+        let timer_width = ui
+            .available_width()
+            .min(ui.available_height() * 0.6 / TIMER_ASPECT_RATIO);
+        let timer_size = Vec2::new(timer_width, timer_width * TIMER_ASPECT_RATIO);
+        let mut leave_focus = ui.input(|i| i.key_pressed(egui::Key::Escape));
+        ui.vertical_centered(|ui| {
+            ui.add_space((ui.available_height() - timer_size.y) / 3.0);
+            self.draw_timer_text_element(ui, timer_size);
+            self.draw_pause_button_element(ui, timer_size.x / 2.0);
+            self.draw_break_activity_element(ui);
+            if ui
+                .add(egui::Button::new(
+                    RichText::new("Exit focus").text_style(TextStyle::Name("Small Text".into())),
+                ))
+                .clicked()
+            {
+                leave_focus = true;
+            }
+        });
+        leave_focus
+    }
+
+    //The countdown and the suggested activity during a break, with an emergency skip if allowed.
+    //Returns true when Esc was held long enough to leave the overlay.
+    pub fn draw_break_screen(&mut self, ui: &mut Ui, since: u64) -> bool {
+        let (escape_down, now) = ui.input(|i| (i.key_down(egui::Key::Escape), i.time));
+        let held = self.escape_hold.update(escape_down, now);
+        if escape_down {
+            ui.ctx().request_repaint();
+        }
+        let timer_width = ui
+            .available_width()
+            .min(ui.available_height() * 0.6 / TIMER_ASPECT_RATIO);
+        let timer_size = Vec2::new(timer_width, timer_width * TIMER_ASPECT_RATIO);
+        ui.vertical_centered(|ui| {
+            ui.add_space((ui.available_height() - timer_size.y) / 3.0);
+            ui.heading(self.timer_data.work_time().to_string());
+            self.draw_timer_text_element(ui, timer_size);
+            self.draw_break_activity_element(ui);
+            let wait = self
+                .settings
+                .break_overlay
+                .skip_wait(since, clock::now_unix());
+            if let Some(wait) = wait {
+                let label = if wait > 0 {
+                    format!("Emergency skip in {} s", wait)
+                } else {
+                    String::from("Emergency skip")
+                };
+                if ui
+                    .add_enabled(
+                        wait == 0,
+                        Button::new(
+                            RichText::new(label).text_style(TextStyle::Name("Small Text".into())),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.timer_data.stop();
+                }
+            }
+            let hint = if escape_down {
+                format!(
+                    "Leaving in {:.0} s",
+                    (ESCAPE_HOLD_SECONDS - held).max(0.0).ceil()
+                )
+            } else {
+                format!("Hold Esc for {} s to leave", ESCAPE_HOLD_SECONDS)
+            };
+            ui.label(RichText::new(hint).text_style(TextStyle::Name("Small Text".into())));
+        });
+        held >= ESCAPE_HOLD_SECONDS
+    }
+
+    //Starts the break that is up next and covers the screen for it.
+    fn enter_break_overlay(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if matches!(self.current_screen, Screen::BreakScreen { .. }) {
+            return;
+        }
+        self.leave_settings_screen(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.current_screen == Screen::MiniScreen {
+            self.leave_mini_mode(frame);
+        }
+        let previous = match self.current_screen {
+            Screen::SettingsScreen { .. } => Screen::TimerScreen,
+            _ => self.current_screen.clone(),
+        };
+        if *self.timer_data.timer_state() == TimerState::Done {
+            self.timer_data.toggle_pause(&self.settings);
+        }
+        self.current_screen = Screen::BreakScreen {
+            previous: Box::new(previous),
+            since: clock::now_unix(),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            frame.set_fullscreen(true);
+            frame.set_always_on_top(true);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = frame;
+    }
+
+    //Goes back to the screen from before the break once it is over or skipped, or right away when
+    //the user `escaped`.
+    fn leave_break_overlay(&mut self, frame: &mut eframe::Frame, escaped: bool) {
+        let on_break = *self.timer_data.work_time() != WorkTimes::Work
+            && matches!(
+                self.timer_data.timer_state(),
+                TimerState::Started(_) | TimerState::Paused(_)
+            );
+        let previous = match &self.current_screen {
+            Screen::BreakScreen { previous, .. } if !on_break || escaped => (**previous).clone(),
+            _ => return,
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            frame.set_always_on_top(false);
+            frame.set_fullscreen(previous == Screen::FocusScreen);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = frame;
+        self.escape_hold = EscapeHold::default();
+        self.current_screen = previous;
+    }
+
+    //Compact view for the always-on-top mini window. The whole window can be used to drag it
+    //around and double-clicking it goes back to the full view.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn draw_mini_screen(&mut self, ui: &mut Ui, frame: &mut eframe::Frame) {
+        //Kept up to date, so the position is remembered also when the app is closed in mini mode.
+        if let Some(pos) = frame.info().window_info.position {
+            self.mini_window_pos = Some(pos);
+        }
+        let background = ui.interact(
+            ui.max_rect(),
+            ui.id().with("mini_window_background"),
+            Sense::click_and_drag(),
+        );
+        if background.drag_started() {
+            frame.drag_window();
+        }
+        let button_width = 15.0;
+        let timer_width = ui.available_width() - button_width - ui.spacing().item_spacing.x;
+        let timer_size = Vec2::new(
+            timer_width,
+            ui.available_height().min(timer_width * TIMER_ASPECT_RATIO),
+        );
+        ui.horizontal(|ui| {
+            self.draw_timer_text_element(ui, timer_size);
+            let button_string = match self.timer_data.timer_state() {
+                TimerState::Started(_) | TimerState::Flowing(_) => "||",
+                _ => ">",
+            };
+            if ui
+                .add_sized([button_width, timer_size.y], Button::new(button_string))
+                .clicked()
+            {
+                self.request(StrictAction::Pause);
+            }
+        });
+        if background.double_clicked() {
+            self.leave_mini_mode(frame);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn enter_mini_mode(&mut self, frame: &mut eframe::Frame) {
+        let window_info = frame.info().window_info;
+        self.normal_window = Some((window_info.position, window_info.size));
+        frame.set_decorations(false);
+        frame.set_always_on_top(true);
+        frame.set_window_size(MINI_WINDOW_SIZE);
+        if let Some(pos) = self.mini_window_pos {
+            frame.set_window_pos(pos);
+        }
+        self.current_screen = Screen::MiniScreen;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn leave_mini_mode(&mut self, frame: &mut eframe::Frame) {
+        frame.set_decorations(true);
+        frame.set_always_on_top(false);
+        if let Some((pos, size)) = self.normal_window.take() {
+            frame.set_window_size(size);
+            if let Some(pos) = pos {
+                frame.set_window_pos(pos);
+            }
+        }
+        self.current_screen = Screen::TimerScreen;
     }
     pub fn draw_settings_screen<'a>(&mut self, ui: &'a mut Ui) {
         let editable_settings = match &mut self.current_screen {
             Screen::SettingsScreen { editable_settings } => Some(editable_settings),
-            Screen::TimerScreen => None,
+            _ => None,
         }
         .unwrap(); //We already know screen is settingsScreen, but borrow checker demands we have
                    //a match statement here.
-        ui.add(egui::TextEdit::singleline(&mut editable_settings[0]));
-        ui.add(egui::TextEdit::singleline(&mut editable_settings[1]));
-        ui.add(egui::TextEdit::singleline(&mut editable_settings[2]));
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.add(egui::TextEdit::singleline(&mut editable_settings.work));
+            ui.add(egui::TextEdit::singleline(
+                &mut editable_settings.long_break,
+            ));
+            ui.add(egui::TextEdit::singleline(
+                &mut editable_settings.short_break,
+            ));
+            ui.add(
+                egui::Slider::new(&mut self.settings.long_break_every, 0..=8)
+                    .text("Work phases per long break (0 is never)"),
+            );
+            //The scale is applied when leaving the settings, rescaling while dragging the slider
+            //would move the slider away from under the cursor.
+            ui.add(egui::Slider::new(&mut self.settings.ui_scale, 1.0..=4.0).text("UI scale"));
+            ui.checkbox(
+                &mut self.settings.auto_pause_on_idle,
+                "Pause work when idle",
+            )
+            .on_hover_text("Only input to this window counts, unless the X11 idle time is used");
+            let mut idle_minutes = self.settings.idle_threshold.as_secs() / 60;
+            if ui
+                .add(egui::Slider::new(&mut idle_minutes, 1..=60).text("Idle minutes"))
+                .changed()
+            {
+                self.settings.idle_threshold = Duration::from_secs(idle_minutes * 60);
+            }
+            #[cfg(target_os = "linux")]
+            ui.checkbox(
+                &mut self.settings.use_system_idle,
+                "Use system idle time (X11 only, skipped under Wayland)",
+            );
+            let breaks = &mut self.settings.flowtime_breaks;
+            ui.horizontal(|ui| {
+                ui.label("Flowtime breaks");
+                ui.radio_value(&mut breaks.use_table, true, "Table");
+                ui.radio_value(&mut breaks.use_table, false, "Ratio");
+            });
+            if breaks.use_table {
+                ui.add(
+                    egui::TextEdit::singleline(&mut editable_settings.flowtime_table)
+                        .hint_text("Work=break minutes, e.g. 25=5, 50=8"),
+                );
+            } else {
+                ui.add(
+                    egui::Slider::new(&mut breaks.ratio, 1.0..=10.0).text("Work per break minute"),
+                );
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut editable_settings.pause_reasons)
+                    .hint_text("Pause reasons, separated by commas"),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut editable_settings.short_break_activities)
+                    .hint_text("Short break activities, separated by commas"),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut editable_settings.long_break_activities)
+                    .hint_text("Long break activities, separated by commas"),
+            );
+            ui.checkbox(
+                &mut self.settings.reflection_prompt,
+                "Reflect after work sessions",
+            );
+            let warning = &mut self.settings.phase_warning;
+            ui.add(
+                egui::Slider::new(&mut warning.before, 0..=300)
+                    .text("Warn seconds before the end (0 is off)"),
+            );
+            if warning.before > 0 {
+                ui.checkbox(&mut warning.breaks, "Also before breaks end");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut warning.sound, "Warning sound");
+                    ui.add_enabled(
+                        warning.sound,
+                        egui::Slider::new(&mut warning.volume, 0.0..=1.0).text("Volume"),
+                    );
+                });
+                ui.checkbox(&mut warning.flash, "Flash the timer");
+            }
+            ui.collapsing("Background sound during work", |ui| {
+                self.settings.ambient_sound.draw_settings(ui);
+            });
+            self.settings.strict_mode.draw_settings(ui);
+            self.settings.break_overlay.draw_settings(ui);
+            ui.checkbox(
+                &mut self.settings.overtime,
+                "Count overtime after a phase ends",
+            );
+            let goal = &mut self.settings.daily_goal;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut goal.unit, GoalUnit::Pomodoros, "Pomodoros");
+                ui.radio_value(&mut goal.unit, GoalUnit::FocusMinutes, "Focus minutes");
+            });
+            let max_target = match goal.unit {
+                GoalUnit::Pomodoros => 20,
+                GoalUnit::FocusMinutes => 600,
+            };
+            goal.target = goal.target.min(max_target);
+            ui.add(
+                egui::Slider::new(&mut goal.target, 0..=max_target).text("Daily goal (0 is off)"),
+            );
+            ui.add(egui::Slider::new(&mut goal.day_start_hour, 0..=23).text("Day starts at hour"));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.settings.calendar_path)
+                        .hint_text("Calendar file (.ics)"),
+                )
+                .on_hover_text("Times with a time zone (TZID) are read as local time");
+                if let Some(err) = &self.calendar_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            let hours = &mut self.settings.working_hours;
+            ui.collapsing("Working hours", |ui| {
+                ui.checkbox(&mut hours.enabled, "Use working hours");
+                ui.checkbox(&mut hours.auto_start, "Start work when a block begins");
+                ui.checkbox(&mut hours.remind, "Remind when a block begins");
+                egui::Grid::new("working_hours_grid").show(ui, |ui| {
+                    for (weekday, name) in DAY_NAMES.iter().enumerate() {
+                        ui.label(*name);
+                        ui.add(
+                            egui::TextEdit::singleline(
+                                &mut editable_settings.working_hours[weekday],
+                            )
+                            .hint_text("e.g. 9:00-12:00, 13:00-17:00"),
+                        );
+                        ui.end_row();
+                    }
+                });
+            });
+        });
     }
+
+    //Starts work or reminds of it when a working hours block begins and no timer is running.
+    //Blocks that began while the computer was asleep past their end are not acted on.
+    fn check_working_hours(&mut self) {
+        let now = clock::now_unix();
+        let last = std::mem::replace(&mut self.schedule_checked, now);
+        //A block that began before the app was started is not caught up on.
+        if last == 0 {
+            return;
+        }
+        let hours = &self.settings.working_hours;
+        let started = match hours.block_started(last, now) {
+            Some(started) => started,
+            None => return,
+        };
+        if *self.timer_data.timer_state() != TimerState::Done || !hours.is_working_time(now) {
+            return;
+        }
+        if hours.auto_start {
+            self.timer_data.set_work_time(WorkTimes::Work);
+            self.timer_data.toggle_pause(&self.settings);
+        } else if hours.remind {
+            self.schedule_reminder = Some(started);
+            //The reminder dialog is shown either way.
+            let _ = self.sound.play_alert(self.settings.alert_sound_setting());
+        }
+    }
+
+    fn draw_schedule_reminder(&mut self, ctx: &egui::Context) {
+        let started = match self.schedule_reminder {
+            Some(started) => started,
+            None => return,
+        };
+        let mut close = false;
+        egui::Window::new("Working hours")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Working hours began at {}, no timer is running.",
+                    planner::format_clock_time(started)
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Start work").clicked() {
+                        if *self.timer_data.timer_state() == TimerState::Done {
+                            self.timer_data.set_work_time(WorkTimes::Work);
+                            self.timer_data.toggle_pause(&self.settings);
+                        }
+                        close = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.schedule_reminder = None;
+        }
+    }
+
+    //Auto-pauses a started work phase once the user has been idle for longer than the threshold.
+    fn check_idle(&mut self, ctx: &egui::Context) {
+        let idle_for = self
+            .idle_detector
+            .idle_time(ctx, self.settings.use_system_idle);
+        let working = *self.timer_data.work_time() == WorkTimes::Work
+            && matches!(
+                self.timer_data.timer_state(),
+                TimerState::Started(_) | TimerState::Flowing(_)
+            );
+        if self.settings.auto_pause_on_idle
+            && working
+            && self.idle_detector.pending().is_none()
+            && idle_for >= self.settings.idle_threshold
+        {
+            let counted = self.timer_data.auto_pause(idle_for);
+            self.idle_detector.start_pending(idle_for, counted);
+        }
+    }
+
+    //Asks the user returning from an auto-pause whether the idle time should count as work.
+    fn draw_idle_dialog(&mut self, ctx: &egui::Context) {
+        let pending = match self.idle_detector.pending() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let away_for = Duration::from_secs(clock::now_unix().saturating_sub(pending.start));
+        let mut keep = None;
+        egui::Window::new("Welcome back")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The timer was paused, you were away for {} min.",
+                    away_for.as_secs() / 60
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Keep").clicked() {
+                        keep = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        keep = Some(false);
+                    }
+                });
+            });
+        if let Some(kept) = keep {
+            self.idle_detector.take_pending();
+            self.timer_data.resolve_idle(
+                IdleGap {
+                    start: pending.start,
+                    duration: away_for,
+                    kept,
+                },
+                pending.counted,
+            );
+        }
+    }
+    //Applies what was typed into the settings screen, does nothing on other screens.
+    fn leave_settings_screen(&mut self, ctx: &egui::Context) {
+        if let Screen::SettingsScreen { editable_settings } = &self.current_screen {
+            TimerApp::validate_work_time_setting(
+                &mut self.settings,
+                &editable_settings.work,
+                &WorkTimes::Work,
+            );
+            TimerApp::validate_work_time_setting(
+                &mut self.settings,
+                &editable_settings.long_break,
+                &WorkTimes::Long,
+            );
+            TimerApp::validate_work_time_setting(
+                &mut self.settings,
+                &editable_settings.short_break,
+                &WorkTimes::Short,
+            );
+            if let Some(table) = FlowtimeBreaks::parse_table(&editable_settings.flowtime_table) {
+                self.settings.flowtime_breaks.table = table;
+            }
+            self.settings.pause_reasons = parse_list(&editable_settings.pause_reasons);
+            for (weekday, text) in editable_settings.working_hours.iter().enumerate() {
+                if let Some(blocks) = WorkingHours::parse_day(text) {
+                    self.settings.working_hours.days[weekday] = blocks;
+                }
+            }
+            self.settings.short_break_activities =
+                parse_list(&editable_settings.short_break_activities);
+            self.settings.long_break_activities =
+                parse_list(&editable_settings.long_break_activities);
+            TimerAppVisuals::set_ui_scale(ctx, self.settings.ui_scale);
+        }
+    }
+
+    pub fn draw_history_screen(&mut self, ui: &mut Ui) {
+        let mut query = String::new();
+        let mut session_filter = SessionFilter::default();
+        if let Screen::HistoryScreen {
+            export_dialog,
+            import_dialog,
+            search,
+            filter,
+        } = &mut self.current_screen
+        {
+            ui.horizontal(|ui| {
+                if ui.button("Export").clicked() {
+                    *export_dialog = Some(Box::default());
+                }
+                if ui.button("Import").clicked() {
+                    *import_dialog = Some(Box::default());
+                }
+                ui.add(egui::TextEdit::singleline(search).hint_text("Search"));
+            });
+            filter.draw(ui, &self.tasks, self.history.sessions());
+            query = search.clone();
+            session_filter = filter.clone();
+        }
+        let project_names: Vec<String> = self
+            .tasks
+            .projects()
+            .iter()
+            .map(|project| project.name.clone())
+            .collect();
+        let mut assigned = None;
+        let (internal, external) = self.history.interruption_counts();
+        ui.label(format!(
+            "Interruptions: {} internal, {} external",
+            internal, external
+        ));
+        egui::CollapsingHeader::new("Pauses").show(ui, |ui| {
+            for (date, paused) in self.history.paused_per_day().iter().rev().take(7) {
+                ui.label(format!("{}: {} min paused", date, paused.as_secs() / 60));
+            }
+            let reasons: Vec<String> = self
+                .history
+                .pause_reason_counts()
+                .iter()
+                .take(5)
+                .map(|(reason, count)| format!("{} ({})", reason, count))
+                .collect();
+            if !reasons.is_empty() {
+                ui.label(format!("Common reasons: {}", reasons.join(", ")));
+            }
+        });
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("history_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for session in self.history.sessions().iter().rev().filter(|session| {
+                        session.matches(&query) && session_filter.matches(session)
+                    }) {
+                        ui.label(clock::DateTime::local(session.start).to_string());
+                        ui.label(session.work_time.to_string());
+                        ui.label(session.task.as_deref().unwrap_or(""));
+                        let color = session
+                            .project
+                            .as_deref()
+                            .and_then(|project| self.tasks.project_color(project))
+                            .unwrap_or(Color32::TRANSPARENT);
+                        ui.colored_label(color, "●");
+                        let mut project = session.project.clone();
+                        tasks::project_combo_box(
+                            ui,
+                            ("session_project", session.work_time, session.start),
+                            &mut project,
+                            &project_names,
+                            "No project",
+                        );
+                        if project != session.project {
+                            assigned = Some((session.work_time, session.start, project));
+                        }
+                        ui.label(TimerData::dur_as_minutes(&session.duration));
+                        ui.label(if session.completed {
+                            "✔"
+                        } else if session.is_abandoned() {
+                            "✖"
+                        } else {
+                            ""
+                        });
+                        ui.label(session.interruption_tally());
+                        match &session.reflection {
+                            Some(reflection) => ui.label(format!(
+                                "Focus {}/5, energy {}/5: {}",
+                                reflection.focus, reflection.energy, reflection.accomplished
+                            )),
+                            None => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some((work_time, start, project)) = assigned {
+            self.history.set_project(work_time, start, project);
+        }
+    }
+
+    pub fn draw_tasks_screen(&mut self, ui: &mut Ui) {
+        let mut edits = TaskEdits::default();
+        if let Screen::TasksScreen {
+            new_task,
+            new_project,
+            import_path,
+            status,
+        } = &mut self.current_screen
+        {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(import_path)
+                            .hint_text("todo.txt or Markdown file"),
+                    );
+                    if ui.button("Import").clicked() {
+                        let path = import_path.trim().to_owned();
+                        *status = Some(match task_files::read_task_file(&path) {
+                            Ok(tasks) => {
+                                let count = tasks.len();
+                                self.tasks.replace_from_file(&path, tasks);
+                                self.task_file_watcher.mark_seen(&path);
+                                format!("Imported {} tasks from {}", count, path)
+                            }
+                            Err(err) => err,
+                        });
+                    }
+                });
+                ui.checkbox(&mut self.tasks.watch_files, "Reload files when they change");
+                let mut removed_file = None;
+                for (index, file) in self.tasks.files.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(file);
+                        if ui.small_button("Forget").clicked() {
+                            removed_file = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed_file {
+                    self.tasks.files.remove(index);
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            let _ = import_path;
+            if let Some(status) = status {
+                ui.label(status.as_str());
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                edits = self.tasks.draw(ui, new_task, new_project);
+            });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut result = Ok(());
+            if let Some(task) = &edits.removed {
+                result = result.and(self.remove_task_line(task));
+            }
+            for index in edits.changed {
+                result = result.and(self.write_back_task(index));
+            }
+            if let Err(err) = result {
+                if let Screen::TasksScreen { status, .. } = &mut self.current_screen {
+                    *status = Some(err);
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = edits;
+    }
+
+    //Writes a task's state back into the file it was imported from.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write_back_task(&mut self, index: usize) -> Result<(), String> {
+        let task = &self.tasks.tasks()[index];
+        task_files::write_back(task)?;
+        if let Some(source) = &task.source {
+            self.task_file_watcher.mark_seen(&source.path);
+        }
+        Ok(())
+    }
+
+    //Takes a deleted task out of the file it was imported from.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn remove_task_line(&mut self, task: &tasks::Task) -> Result<(), String> {
+        task_files::remove_line(task)?;
+        if let Some(source) = &task.source {
+            self.task_file_watcher.mark_seen(&source.path);
+        }
+        Ok(())
+    }
+
+    //Re-imports watched task files that changed on disk.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_task_files(&mut self, ctx: &egui::Context) {
+        if !self.tasks.watch_files {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        for path in self.task_file_watcher.changed_files(&self.tasks.files, now) {
+            if let Ok(tasks) = task_files::read_task_file(&path) {
+                self.tasks.replace_from_file(&path, tasks);
+            }
+        }
+    }
+
+    //Weekly numbers with buttons to step through the weeks and to save or copy the report.
+    pub fn draw_stats_screen(&mut self, ui: &mut Ui) {
+        let (week_start, status, filter) = match &mut self.current_screen {
+            Screen::StatsScreen {
+                week_start,
+                status,
+                filter,
+            } => (week_start, status, filter),
+            _ => return,
+        };
+        filter.draw(ui, &self.tasks, self.history.sessions());
+        let report = WeeklyReport::new(&self.history, *week_start, filter);
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                *week_start = week_start.add_days(-7);
+            }
+            ui.label(format!("{} – {}", week_start, week_start.add_days(6)));
+            if ui.button(">").clicked() {
+                *week_start = week_start.add_days(7);
+            }
+        });
+        egui::Grid::new("stats_days_grid").show(ui, |ui| {
+            for (day, focus) in report.focus_per_day.iter().enumerate() {
+                ui.label(week_start.add_days(day as i64).to_string());
+                ui.label(report::format_focus(*focus));
+                ui.end_row();
+            }
+        });
+        ui.label(format!(
+            "Total: {}",
+            report::format_focus(report.total_focus())
+        ));
+        match report.completion_rate() {
+            Some(rate) => ui.label(format!(
+                "{}/{} sessions completed ({}%)",
+                report.completed_sessions, report.work_sessions, rate
+            )),
+            None => ui.label("No work sessions"),
+        };
+        ui.label(format!(
+            "Interruptions: {} internal, {} external",
+            report.internal_interruptions, report.external_interruptions
+        ));
+        let best_hours: Vec<String> = report
+            .best_hours()
+            .iter()
+            .map(|(hour, _)| format!("{:02}:00", hour))
+            .collect();
+        if !best_hours.is_empty() {
+            ui.label(format!("Best hours: {}", best_hours.join(", ")));
+        }
+        if !report.break_activities.is_empty() {
+            ui.label(format!("Break activities: {}", report.activities_text()));
+        }
+        ui.horizontal(|ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            for extension in ["md", "html"] {
+                if ui.button(format!("Save {}", extension)).clicked() {
+                    let path = format!("weekly_report_{}.{}", week_start, extension);
+                    let text = if extension == "md" {
+                        report.to_markdown()
+                    } else {
+                        report.to_html()
+                    };
+                    *status = Some(match std::fs::write(&path, text) {
+                        Ok(()) => format!("Saved to {}", path),
+                        Err(err) => format!("Could not save: {}", err),
+                    });
+                }
+            }
+            if ui.button("Copy markdown").clicked() {
+                ui.output_mut(|o| o.copied_text = report.to_markdown());
+                *status = Some(String::from("Copied to clipboard"));
+            }
+        });
+        if let Some(status) = status {
+            ui.label(status.as_str());
+        }
+    }
+
+    fn draw_history_dialogs(&mut self, ctx: &egui::Context) {
+        if let Screen::HistoryScreen {
+            export_dialog,
+            import_dialog,
+            ..
+        } = &mut self.current_screen
+        {
+            if let Some(dialog) = export_dialog {
+                if !dialog.show(ctx, &self.history) {
+                    *export_dialog = None;
+                }
+            }
+            if let Some(dialog) = import_dialog {
+                if !dialog.show(ctx, &mut self.history) {
+                    *import_dialog = None;
+                }
+            }
+        }
+    }
+
     pub fn validate_work_time_setting(
         settings: &mut Setting,
         new_val: &String,
@@ -233,78 +1806,250 @@ impl eframe::App for TimerApp {
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        //here we should somehow show the error in some popup dialog!
-        self.timer_data.update(&self.settings);
-
-        // no File->Quit on web pages!
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            // The top panel is often a good place for a menu bar:
-            egui::menu::bar(ui, |ui| {
-                //Size of button is bugged/weird, make a custom menu_button?
-                #[cfg(not(target_arch = "wasm32"))]
-                ui.menu_button("X", |ui| {
+        if let Some(event) = self.timer_data.update(&self.settings) {
+            //here we should somehow show the error in some popup dialog!
+            let _ = match event {
+                TimerEvent::Ended => self.sound.play_alert(self.settings.alert_sound_setting()),
+                TimerEvent::Warning => {
+                    let warning = &self.settings.phase_warning;
+                    if warning.flash {
+                        self.flash_until = ctx.input(|i| i.time) + WARNING_FLASH_SECONDS;
+                    }
+                    if warning.sound {
+                        self.sound
+                            .play_sound(self.settings.alert_sound_setting(), warning.volume)
+                    } else {
+                        Ok(())
+                    }
+                }
+            };
+        }
+        let working = *self.timer_data.work_time() == WorkTimes::Work
+            && matches!(
+                self.timer_data.timer_state(),
+                TimerState::Started(_) | TimerState::Flowing(_)
+            );
+        self.sound
+            .update_ambient(&self.settings.ambient_sound, working);
+        self.timer_visuals.update_phase_visuals(
+            ctx,
+            &self.color_scheme,
+            self.timer_data.work_time(),
+        );
+        self.update_window_title(_frame);
+        #[cfg(all(unix, not(target_arch = "wasm32")))]
+        self.handle_control_commands();
+        self.check_idle(ctx);
+        self.check_working_hours();
+        self.update_meetings(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.reload_task_files(ctx);
+
+        let show_menu_bar = match self.current_screen {
+            Screen::FocusScreen | Screen::BreakScreen { .. } => false,
+            #[cfg(not(target_arch = "wasm32"))]
+            Screen::MiniScreen => false,
+            _ => true,
+        };
+        if show_menu_bar {
+            // no File->Quit on web pages!
+            egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+                // The top panel is often a good place for a menu bar:
+                egui::menu::bar(ui, |ui| {
+                    //Size of button is bugged/weird, make a custom menu_button?
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.menu_button("X", |ui| {
+                        if ui
+                            .add_sized(
+                                [20.0, 10.0],
+                                egui::Button::new(
+                                    RichText::new("Confirm?")
+                                        .text_style(TextStyle::Name("Small Text".into())),
+                                ),
+                            )
+                            .clicked()
+                        {
+                            _frame.close();
+                        }
+                    });
+
                     if ui
-                        .add_sized(
-                            [20.0, 10.0],
-                            egui::Button::new(
-                                RichText::new("Confirm?")
-                                    .text_style(TextStyle::Name("Small Text".into())),
-                            ),
-                        )
+                        .add(egui::SelectableLabel::new(
+                            matches!(self.current_screen, Screen::SettingsScreen { .. }),
+                            "Settings",
+                        ))
                         .clicked()
                     {
-                        _frame.close();
+                        if matches!(self.current_screen, Screen::SettingsScreen { .. }) {
+                            self.leave_settings_screen(ctx);
+                            self.current_screen = Screen::TimerScreen
+                        } else {
+                            self.current_screen = Screen::SettingsScreen {
+                                editable_settings: Box::new(TimerData::load_editable_settings(
+                                    &self.settings,
+                                )),
+                            }
+                        }
                     }
-                });
 
-                if ui
-                    .add(egui::SelectableLabel::new(
-                        self.current_screen != Screen::TimerScreen,
-                        "Settings",
-                    ))
-                    .clicked()
-                {
-                    match &self.current_screen {
-                        Screen::SettingsScreen { editable_settings } => {
-                            TimerApp::validate_work_time_setting(
-                                &mut self.settings,
-                                &editable_settings[0],
-                                &WorkTimes::Work,
-                            );
-                            TimerApp::validate_work_time_setting(
-                                &mut self.settings,
-                                &editable_settings[1],
-                                &WorkTimes::Long,
-                            );
-                            TimerApp::validate_work_time_setting(
-                                &mut self.settings,
-                                &editable_settings[2],
-                                &WorkTimes::Short,
-                            );
+                    if ui
+                        .add(egui::SelectableLabel::new(
+                            matches!(self.current_screen, Screen::HistoryScreen { .. }),
+                            "History",
+                        ))
+                        .clicked()
+                    {
+                        if matches!(self.current_screen, Screen::HistoryScreen { .. }) {
                             self.current_screen = Screen::TimerScreen
+                        } else {
+                            self.leave_settings_screen(ctx);
+                            self.current_screen = Screen::HistoryScreen {
+                                export_dialog: None,
+                                import_dialog: None,
+                                search: String::new(),
+                                filter: SessionFilter::default(),
+                            }
                         }
-                        Screen::TimerScreen => {
-                            self.current_screen = Screen::SettingsScreen {
-                                editable_settings: TimerData::load_editable_settings(
-                                    &self.settings,
-                                ),
+                    }
+
+                    if ui
+                        .add(egui::SelectableLabel::new(
+                            matches!(self.current_screen, Screen::StatsScreen { .. }),
+                            "Stats",
+                        ))
+                        .clicked()
+                    {
+                        if matches!(self.current_screen, Screen::StatsScreen { .. }) {
+                            self.current_screen = Screen::TimerScreen
+                        } else {
+                            self.leave_settings_screen(ctx);
+                            self.current_screen = Screen::StatsScreen {
+                                week_start: report::this_week(),
+                                status: None,
+                                filter: SessionFilter::default(),
                             }
                         }
                     }
-                }
+
+                    if ui
+                        .add(egui::SelectableLabel::new(
+                            matches!(self.current_screen, Screen::TasksScreen { .. }),
+                            "Tasks",
+                        ))
+                        .clicked()
+                    {
+                        if matches!(self.current_screen, Screen::TasksScreen { .. }) {
+                            self.current_screen = Screen::TimerScreen
+                        } else {
+                            self.leave_settings_screen(ctx);
+                            self.current_screen = Screen::TasksScreen {
+                                new_task: String::new(),
+                                new_project: String::new(),
+                                import_path: String::new(),
+                                status: None,
+                            }
+                        }
+                    }
+
+                    if ui
+                        .add(egui::SelectableLabel::new(
+                            matches!(self.current_screen, Screen::PlannerScreen),
+                            "Planner",
+                        ))
+                        .clicked()
+                    {
+                        if matches!(self.current_screen, Screen::PlannerScreen) {
+                            self.current_screen = Screen::TimerScreen
+                        } else {
+                            self.leave_settings_screen(ctx);
+                            self.current_screen = Screen::PlannerScreen
+                        }
+                    }
+
+                    if ui.add(egui::SelectableLabel::new(false, "Focus")).clicked() {
+                        self.leave_settings_screen(ctx);
+                        self.current_screen = Screen::FocusScreen;
+                        #[cfg(not(target_arch = "wasm32"))]
+                        _frame.set_fullscreen(true);
+                    }
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.add(egui::SelectableLabel::new(false, "Mini")).clicked() {
+                        self.leave_settings_screen(ctx);
+                        self.enter_mini_mode(_frame);
+                    }
+                });
             });
-        });
+        }
         let cur_screen = self.current_screen.clone();
+        let mut leave_focus = false;
+        let mut leave_break = false;
         egui::CentralPanel::default().show(ctx, |ui| {
             match cur_screen {
                 Screen::TimerScreen => self.draw_timer_screen(ui),
+                Screen::FocusScreen => leave_focus = self.draw_focus_screen(ui),
+                Screen::BreakScreen { since, .. } => {
+                    leave_break = self.draw_break_screen(ui, since)
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                Screen::MiniScreen => self.draw_mini_screen(ui, _frame),
                 Screen::SettingsScreen { editable_settings } => {
                     //We cant use editable_settings in function call directly due to the borrow
                     //checker
                     self.draw_settings_screen(ui);
                 }
+                Screen::HistoryScreen { .. } => self.draw_history_screen(ui),
+                Screen::StatsScreen { .. } => self.draw_stats_screen(ui),
+                Screen::TasksScreen { .. } => self.draw_tasks_screen(ui),
+                Screen::PlannerScreen => self.draw_planner_screen(ui),
             }
         });
+        if leave_focus {
+            self.current_screen = Screen::TimerScreen;
+            #[cfg(not(target_arch = "wasm32"))]
+            _frame.set_fullscreen(false);
+        }
+        self.draw_idle_dialog(ctx);
+        self.draw_interruption_dialog(ctx);
+        self.draw_history_dialogs(ctx);
+        self.draw_reflection_dialog(ctx);
+        self.draw_schedule_reminder(ctx);
+        self.draw_override_dialog(ctx);
+        let mut break_is_next = false;
+        for session in self.timer_data.take_finished_sessions() {
+            break_is_next = session.completed && session.work_time == WorkTimes::Work;
+            if session.work_time != WorkTimes::Work {
+                self.activity_turn += 1;
+            }
+            if self.settings.reflection_prompt
+                && session.completed
+                && session.work_time == WorkTimes::Work
+            {
+                self.pending_reflection = Some((session.start, Reflection::default()));
+            }
+            if session.completed && session.work_time == WorkTimes::Work {
+                if let Some(index) = session
+                    .task
+                    .as_deref()
+                    .and_then(|task| self.tasks.count_pomodoro(task))
+                {
+                    //There is nowhere to show an error from here, the count is still kept in
+                    //the task list and written with the next change.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let _ = self.write_back_task(index);
+                    #[cfg(target_arch = "wasm32")]
+                    let _ = index;
+                }
+            }
+            self.history.push(session);
+        }
+        if break_is_next
+            && self.settings.break_overlay.enabled
+            && *self.timer_data.work_time() != WorkTimes::Work
+        {
+            self.enter_break_overlay(ctx, _frame);
+        }
+        self.leave_break_overlay(_frame, leave_break);
 
         #[cfg(not(target_arch = "wasm32"))]
         if _frame.info().window_info.focused {
@@ -316,3 +2061,159 @@ impl eframe::App for TimerApp {
         ctx.request_repaint_after(Duration::from_secs(1));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ui_scale_is_applied_when_leaving_settings() {
+        let mut app = TimerApp::default();
+        app.settings.ui_scale = 1.5;
+        app.current_screen = Screen::SettingsScreen {
+            editable_settings: Box::new(TimerData::load_editable_settings(&app.settings)),
+        };
+        let ctx = egui::Context::default();
+        app.leave_settings_screen(&ctx);
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+        assert_eq!(ctx.pixels_per_point(), 1.5);
+    }
+
+    #[test]
+    fn window_title_shows_the_countdown() {
+        let mut app = TimerApp::default();
+        assert_eq!(app.timer_title(), "Pomodoro Timer");
+        app.timer_data.toggle_pause(&app.settings);
+        *app.timer_data.timer_state_mut() = TimerState::Paused(Duration::from_secs(60));
+        let left = app.timer_data.target() - Duration::from_secs(60);
+        assert_eq!(
+            app.timer_title(),
+            format!(
+                "{} – Work (paused)",
+                TimerData::dur_as_minutes(&left).trim()
+            )
+        );
+        *app.timer_data.work_time_mut() = WorkTimes::Short;
+        assert!(app.timer_title().ends_with(" – Short break (paused)"));
+    }
+
+    #[test]
+    fn mini_window_position_is_saved() {
+        let app = TimerApp {
+            mini_window_pos: Some(Pos2::new(40.0, 60.0)),
+            current_screen: Screen::MiniScreen,
+            ..TimerApp::default()
+        };
+        let saved: TimerApp = ron::from_str(&ron::to_string(&app).unwrap()).unwrap();
+        assert_eq!(saved.mini_window_pos, Some(Pos2::new(40.0, 60.0)));
+        assert!(saved.current_screen == Screen::TimerScreen);
+    }
+
+    #[test]
+    fn settings_text_is_applied_when_leaving() {
+        let mut app = TimerApp::default();
+        let mut editable = TimerData::load_editable_settings(&app.settings);
+        assert_eq!(editable.work, "25:00");
+        editable.work = String::from("50:00");
+        editable.short_break = String::from("five");
+        editable.pause_reasons = String::from("Lunch, , Call");
+        editable.working_hours[2] = String::from("9:00-12:00");
+        editable.working_hours[3] = String::from("9:00-");
+        app.current_screen = Screen::SettingsScreen {
+            editable_settings: Box::new(editable),
+        };
+        app.leave_settings_screen(&egui::Context::default());
+        let work_times = app.settings.work_time_settings();
+        assert_eq!(work_times[&WorkTimes::Work], Duration::from_secs(50 * 60));
+        assert_eq!(work_times[&WorkTimes::Short], Duration::from_secs(5 * 60));
+        assert_eq!(app.settings.pause_reasons, ["Lunch", "Call"]);
+        let editable = TimerData::load_editable_settings(&app.settings);
+        assert_eq!(editable.working_hours[2], "9:00-12:00");
+        assert_eq!(
+            editable.working_hours[3],
+            TimerData::load_editable_settings(&Setting::default()).working_hours[3]
+        );
+    }
+
+    #[test]
+    fn break_activities_are_recorded_on_breaks_only() {
+        let mut app = TimerApp::default();
+        assert!(app.settings.break_activities(WorkTimes::Work).is_empty());
+        let mut editable = TimerData::load_editable_settings(&app.settings);
+        editable.long_break_activities = String::from("Walk, , Nap");
+        app.current_screen = Screen::SettingsScreen {
+            editable_settings: Box::new(editable),
+        };
+        app.leave_settings_screen(&egui::Context::default());
+        assert_eq!(
+            app.settings.break_activities(WorkTimes::Long),
+            ["Walk", "Nap"]
+        );
+        app.timer_data.toggle_pause(&app.settings);
+        app.timer_data
+            .set_break_activity(Some(String::from("Walk")));
+        assert_eq!(app.timer_data.active_session().unwrap().activity, None);
+        app.timer_data.stop();
+        app.timer_data.set_work_time(WorkTimes::Long);
+        app.timer_data.toggle_pause(&app.settings);
+        app.timer_data
+            .set_break_activity(Some(String::from("Walk")));
+        assert_eq!(
+            app.timer_data.active_session().unwrap().activity.as_deref(),
+            Some("Walk")
+        );
+    }
+
+    #[test]
+    fn strict_mode_override_is_for_one_action() {
+        let mut app = TimerApp::default();
+        app.settings.strict_mode.enabled = true;
+        assert!(!app.needs_override(StrictAction::Pause));
+        app.request(StrictAction::Pause);
+        assert!(app.needs_override(StrictAction::Pause));
+        assert!(app.needs_override(StrictAction::Skip));
+        app.request(StrictAction::Pause);
+        assert!(app.override_dialog.is_some());
+        app.confirm_override();
+        assert!(app.override_dialog.is_none());
+        assert!(matches!(
+            app.timer_data.timer_state(),
+            TimerState::Paused(_)
+        ));
+        assert!(!app.needs_override(StrictAction::Pause));
+        assert!(app.needs_override(StrictAction::Skip));
+        app.request(StrictAction::Pause);
+        assert!(matches!(
+            app.timer_data.timer_state(),
+            TimerState::Started(_)
+        ));
+        assert!(app.needs_override(StrictAction::Pause));
+        assert!(app.needs_override(StrictAction::SwitchTo(WorkTimes::Short)));
+        //The paused session still runs to its end and is not given up on.
+        app.timer_data.shorten_to(Duration::ZERO);
+        app.timer_data.update(&app.settings);
+        app.timer_data.stop();
+        let sessions = app.timer_data.take_finished_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].completed);
+        assert_eq!(sessions[0].overrides.len(), 1);
+        assert_eq!(sessions[0].overrides[0].action, "Pause");
+        assert!(!sessions[0].is_abandoned());
+    }
+
+    #[test]
+    fn override_is_dropped_when_the_phase_ends() {
+        let mut app = TimerApp::default();
+        app.settings.strict_mode.enabled = true;
+        app.request(StrictAction::Pause);
+        app.request(StrictAction::Skip);
+        assert!(app.override_dialog.is_some());
+        app.timer_data.stop();
+        app.confirm_override();
+        assert!(app.override_dialog.is_some());
+        assert!(app.timer_data.timer_state() == &TimerState::Done);
+        let sessions = app.timer_data.take_finished_sessions();
+        assert!(sessions[0].overrides.is_empty());
+        assert!(!sessions[0].is_abandoned());
+    }
+}
@@ -0,0 +1,89 @@
+//Holding Esc this long always leaves the overlay, whatever the settings, so that it can never lock
+//the user out. The break itself goes on.
+pub const ESCAPE_HOLD_SECONDS: f64 = 3.0;
+
+//Settings for the full screen, always on top view that covers everything else during breaks, so
+//that breaks are actually taken.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BreakOverlay {
+    pub enabled: bool,
+    //Allow leaving the break early once it has lasted `skip_delay` seconds.
+    pub emergency_skip: bool,
+    pub skip_delay: u32,
+}
+
+impl Default for BreakOverlay {
+    fn default() -> Self {
+        BreakOverlay {
+            enabled: false,
+            emergency_skip: true,
+            skip_delay: 30,
+        }
+    }
+}
+
+impl BreakOverlay {
+    pub fn draw_settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Cover the screen during breaks");
+        if !self.enabled {
+            return;
+        }
+        ui.checkbox(&mut self.emergency_skip, "Allow an emergency skip");
+        if self.emergency_skip {
+            ui.add(
+                egui::Slider::new(&mut self.skip_delay, 0..=300).text("Seconds before skipping"),
+            );
+        }
+    }
+
+    //Seconds left until the break can be skipped, None if it cannot be skipped at all.
+    pub fn skip_wait(&self, since: u64, now: u64) -> Option<u64> {
+        self.emergency_skip
+            .then(|| (since + u64::from(self.skip_delay)).saturating_sub(now))
+    }
+}
+
+//Keeps track of how long Esc has been held down on the overlay.
+#[derive(Default)]
+pub struct EscapeHold {
+    since: Option<f64>,
+}
+
+impl EscapeHold {
+    //Called every frame with whether Esc is down, returns for how many seconds it has been held.
+    pub fn update(&mut self, down: bool, now: f64) -> f64 {
+        if !down {
+            self.since = None;
+            return 0.0;
+        }
+        now - *self.since.get_or_insert(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_before_skipping() {
+        let overlay = BreakOverlay::default();
+        assert_eq!(overlay.skip_wait(100, 110), Some(20));
+        assert_eq!(overlay.skip_wait(100, 200), Some(0));
+        let no_skip = BreakOverlay {
+            emergency_skip: false,
+            ..BreakOverlay::default()
+        };
+        assert_eq!(no_skip.skip_wait(100, 200), None);
+    }
+
+    #[test]
+    fn counts_how_long_escape_is_held() {
+        let mut hold = EscapeHold::default();
+        assert_eq!(hold.update(false, 1.0), 0.0);
+        assert_eq!(hold.update(true, 2.0), 0.0);
+        assert_eq!(hold.update(true, 4.5), 2.5);
+        assert_eq!(hold.update(false, 5.0), 0.0);
+        assert_eq!(hold.update(true, 6.0), 0.0);
+    }
+}
@@ -0,0 +1,450 @@
+//Meetings read from an iCalendar (.ics) file exported from a calendar, see RFC 5545. Only what is
+//needed to know when one is busy is read: start, end, title and simple daily or weekly repeats.
+use crate::clock::{self, Date, DateTime};
+use std::time::Duration;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Meeting {
+    pub start: u64,
+    pub end: u64,
+    pub title: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Repeat {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<u32>,
+    until: Option<u64>,
+    //Weekdays of a weekly repeat, 0 is monday. Empty means the weekday of the first meeting.
+    weekdays: Vec<u32>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Event {
+    start: u64,
+    length: u64,
+    title: String,
+    repeat: Option<Repeat>,
+    excluded: Vec<u64>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Calendar {
+    events: Vec<Event>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_calendar(path: &str) -> Result<Calendar, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+    Ok(Calendar::parse(&text))
+}
+
+impl Calendar {
+    //Events without a start time (all-day ones), cancelled ones, ones that do not block time and
+    //ones with times out of range are left out.
+    pub fn parse(text: &str) -> Calendar {
+        let mut events = Vec::new();
+        let mut properties: Option<Vec<(String, String, String)>> = None;
+        for line in unfold_lines(text) {
+            let (name, params, value) = match split_property(&line) {
+                Some(property) => property,
+                None => continue,
+            };
+            match (name.as_str(), value.as_str()) {
+                ("BEGIN", "VEVENT") => properties = Some(Vec::new()),
+                ("END", "VEVENT") => {
+                    if let Some(event) = properties.take().and_then(|props| parse_event(&props)) {
+                        events.push(event);
+                    }
+                }
+                _ => {
+                    if let Some(properties) = &mut properties {
+                        properties.push((name, params, value));
+                    }
+                }
+            }
+        }
+        Calendar { events }
+    }
+
+    //Meetings overlapping the time from `from` to `to`, sorted by start.
+    pub fn meetings_between(&self, from: u64, to: u64) -> Vec<Meeting> {
+        let mut meetings: Vec<Meeting> = self
+            .events
+            .iter()
+            .flat_map(|event| event.starts_until(to))
+            .filter(|(start, event)| {
+                start.saturating_add(event.length) > from && !event.excluded.contains(start)
+            })
+            .map(|(start, event)| Meeting {
+                start,
+                end: start.saturating_add(event.length),
+                title: event.title.clone(),
+            })
+            .collect();
+        meetings.sort_by_key(|meeting| meeting.start);
+        meetings
+    }
+}
+
+impl Event {
+    //Starts of the event's meetings up to `to`, repeats keep the local wall clock time.
+    fn starts_until(&self, to: u64) -> Vec<(u64, &Event)> {
+        let repeat = match &self.repeat {
+            Some(repeat) => repeat,
+            None if self.start <= to => return vec![(self.start, self)],
+            None => return Vec::new(),
+        };
+        let first = DateTime::local(self.start);
+        let first_day = first.date.to_days();
+        let last_day = DateTime::local(to.min(repeat.until.unwrap_or(to)))
+            .date
+            .to_days();
+        let weekdays = if repeat.weekdays.is_empty() {
+            vec![first.date.weekday()]
+        } else {
+            repeat.weekdays.clone()
+        };
+        let mut starts = Vec::new();
+        let mut count = 0;
+        for day in first_day..=last_day {
+            let date = Date::from_days(day);
+            let repeats = match repeat.frequency {
+                Frequency::Daily => (day - first_day) % repeat.interval == 0,
+                Frequency::Weekly => {
+                    let weeks = (day - i64::from(date.weekday()) - first_day
+                        + i64::from(first.date.weekday()))
+                        / 7;
+                    weeks % repeat.interval == 0 && weekdays.contains(&date.weekday())
+                }
+            };
+            if !repeats {
+                continue;
+            }
+            if repeat.count.map_or(false, |limit| count >= limit) {
+                break;
+            }
+            count += 1;
+            let start = clock::local_to_unix(date, first.seconds_of_day());
+            if start <= to && repeat.until.map_or(true, |until| start <= until) {
+                starts.push((start, self));
+            }
+        }
+        starts
+    }
+}
+
+//Long lines are folded onto lines starting with a space or tab.
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (
+            line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+//"DTSTART;TZID=Europe/Berlin:20261019T090000" into the name, parameters and value.
+fn split_property(line: &str) -> Option<(String, String, String)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((
+        name.trim().to_uppercase(),
+        params.to_uppercase(),
+        value.trim().to_owned(),
+    ))
+}
+
+fn parse_event(properties: &[(String, String, String)]) -> Option<Event> {
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(property, _, _)| property == name)
+            .map(|(_, params, value)| (params.as_str(), value.as_str()))
+    };
+    if get("STATUS").map_or(false, |(_, status)| status == "CANCELLED")
+        || get("TRANSP").map_or(false, |(_, transp)| transp == "TRANSPARENT")
+    {
+        return None;
+    }
+    let (params, value) = get("DTSTART")?;
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    let start = parse_date_time(value)?;
+    let end = match (get("DTEND"), get("DURATION")) {
+        (Some((_, end)), _) => parse_date_time(end)?,
+        (None, Some((_, duration))) => start.checked_add(parse_duration(duration)?.as_secs())?,
+        (None, None) => start,
+    };
+    if end <= start {
+        return None;
+    }
+    let excluded = properties
+        .iter()
+        .filter(|(property, _, _)| property == "EXDATE")
+        .flat_map(|(_, _, value)| value.split(',').filter_map(parse_date_time))
+        .collect();
+    Some(Event {
+        start,
+        length: end - start,
+        title: get("SUMMARY").map_or("Meeting".to_owned(), |(_, title)| unescape(title)),
+        repeat: get("RRULE").and_then(|(_, rule)| parse_repeat(rule)),
+        excluded,
+    })
+}
+
+//"20261019T090000Z" is UTC. Times with a TZID are taken as local time, there is no time zone
+//database to look them up in, which is right for calendars exported in one's own time zone.
+fn parse_date_time(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, time) = text.split_once('T')?;
+    if date.len() != 8 || time.len() < 6 || !text.is_ascii() {
+        return None;
+    }
+    let date = Date::parse(&format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))?;
+    let number = |range: std::ops::Range<usize>| time.get(range)?.parse::<u32>().ok();
+    let (hour, minute, second) = (number(0..2)?, number(2..4)?, number(4..6)?);
+    //60 is a leap second.
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let seconds = hour * 3600 + minute * 60 + second;
+    if time.ends_with('Z') {
+        Some((date.to_days() * 86400 + i64::from(seconds)).max(0) as u64)
+    } else {
+        Some(clock::local_to_unix(date, seconds))
+    }
+}
+
+//"PT1H30M" or "P1D"
+fn parse_duration(text: &str) -> Option<Duration> {
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in text.trim().trim_start_matches(['+', 'P']).chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let unit = match c {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                seconds = number
+                    .parse::<u64>()
+                    .ok()?
+                    .checked_mul(unit)
+                    .and_then(|part| part.checked_add(seconds))?;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+//"FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20261231T000000Z", other frequencies are not
+//repeated.
+fn parse_repeat(rule: &str) -> Option<Repeat> {
+    let mut repeat = Repeat {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        weekdays: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in rule.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.to_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => repeat.interval = value.parse::<i64>().ok()?.max(1),
+            "COUNT" => repeat.count = value.parse().ok(),
+            //UNTIL may also be a plain date, which includes meetings on that day.
+            "UNTIL" => {
+                repeat.until =
+                    parse_date_time(value).or_else(|| parse_date_time(&format!("{}T235959", value)))
+            }
+            "BYDAY" => {
+                repeat.weekdays = value
+                    .split(',')
+                    .filter_map(|day| {
+                        //"MO", or "1MO" for monthly rules, the number is ignored.
+                        let day = day.trim_start_matches(|c: char| !c.is_ascii_alphabetic());
+                        ["MO", "TU", "WE", "TH", "FR", "SA", "SU"]
+                            .iter()
+                            .position(|name| name.eq_ignore_ascii_case(day))
+                            .map(|weekday| weekday as u32)
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    repeat.frequency = frequency?;
+    Some(repeat)
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(date: &str, hour: u32, minute: u32) -> u64 {
+        clock::local_to_unix(Date::parse(date).unwrap(), hour * 3600 + minute * 60)
+    }
+
+    fn calendar(events: &[&str]) -> Calendar {
+        let events: Vec<String> = events
+            .iter()
+            .map(|event| format!("BEGIN:VEVENT\r\n{}\r\nEND:VEVENT\r\n", event))
+            .collect();
+        Calendar::parse(&format!(
+            "BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n",
+            events.concat()
+        ))
+    }
+
+    #[test]
+    fn reads_single_events() {
+        let calendar = calendar(&[
+            "DTSTART;TZID=Europe/Berlin:20261019T090000\r\nDTEND:20261019T093000\r\nSUMMARY:Stand\r\n up\\, daily",
+            "DTSTART:20261019T120000Z\r\nDURATION:PT1H30M",
+            "DTSTART;VALUE=DATE:20261019\r\nSUMMARY:All day",
+            "DTSTART:20261019T150000\r\nDTEND:20261019T160000\r\nSTATUS:CANCELLED",
+            "DTSTART:20261019T150000\r\nDTEND:20261019T160000\r\nTRANSP:TRANSPARENT",
+            "DTSTART:20261019T150000\r\nDTEND:20261019T140000",
+        ]);
+        let meetings = calendar.meetings_between(0, u64::MAX);
+        //Which one comes first depends on the local time zone.
+        let titled = |title: &str| {
+            meetings
+                .iter()
+                .find(|meeting| meeting.title == title)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            titled("Standup, daily"),
+            Meeting {
+                start: local("2026-10-19", 9, 0),
+                end: local("2026-10-19", 9, 30),
+                title: String::from("Standup, daily"),
+            }
+        );
+        let utc_noon = Date::parse("2026-10-19").unwrap().to_days() as u64 * 86400 + 12 * 3600;
+        assert_eq!(titled("Meeting").start, utc_noon);
+        assert_eq!(titled("Meeting").end, utc_noon + 5400);
+        assert_eq!(meetings.len(), 2);
+    }
+
+    #[test]
+    fn repeats_events() {
+        let calendar = calendar(&[
+            "DTSTART:20261019T100000\r\nDTEND:20261019T101500\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE\r\nEXDATE:20261021T100000",
+            "DTSTART:20261019T160000\r\nDTEND:20261019T163000\r\nRRULE:FREQ=DAILY;INTERVAL=2;COUNT=2",
+            "DTSTART:20261019T080000\r\nDTEND:20261019T083000\r\nRRULE:FREQ=DAILY;UNTIL=20261020",
+            "DTSTART:20261019T070000\r\nDTEND:20261019T073000\r\nRRULE:FREQ=MONTHLY",
+        ]);
+        let starts: Vec<u64> = calendar
+            .meetings_between(local("2026-10-19", 0, 0), local("2026-10-27", 0, 0))
+            .iter()
+            .map(|meeting| meeting.start)
+            .collect();
+        assert_eq!(
+            starts,
+            vec![
+                local("2026-10-19", 7, 0),
+                local("2026-10-19", 8, 0),
+                local("2026-10-19", 10, 0),
+                local("2026-10-19", 16, 0),
+                local("2026-10-20", 8, 0),
+                local("2026-10-21", 16, 0),
+                local("2026-10-26", 10, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_events_out_of_range() {
+        let calendar = calendar(&[
+            "DTSTART:99991231T235959Z\r\nDURATION:P99999999999999999W",
+            "DTSTART:99991231T235959Z\r\nDURATION:P18446744073709551615D",
+            "DTSTART:20261019T100000\r\nDURATION:PT1X",
+            "DTSTART:2026101T100000\r\nDURATION:PT1H",
+            "DTSTART:20261019T25000\r\nDURATION:PT1H",
+            "DTSTART:20261019T100000\r\nDTEND:20261019T110000\r\nRRULE:FREQ=DAILY;INTERVAL=x\r\nSUMMARY:Once",
+        ]);
+        let meetings = calendar.meetings_between(0, u64::MAX);
+        assert_eq!(meetings.len(), 1);
+        assert_eq!(meetings[0].title, "Once");
+        let endless = Calendar {
+            events: vec![Event {
+                start: 100,
+                length: u64::MAX,
+                title: String::from("Endless"),
+                repeat: None,
+                excluded: Vec::new(),
+            }],
+        };
+        assert_eq!(endless.meetings_between(0, u64::MAX)[0].end, u64::MAX);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("P1DT2S"), Some(Duration::from_secs(86402)));
+        assert_eq!(
+            parse_duration("+P2W"),
+            Some(Duration::from_secs(14 * 86400))
+        );
+        assert_eq!(parse_duration("P99999999999999999W"), None);
+        assert_eq!(parse_duration("PT1Y"), None);
+    }
+
+    #[test]
+    fn parses_date_times() {
+        assert_eq!(
+            parse_date_time("20261019T093000"),
+            Some(local("2026-10-19", 9, 30))
+        );
+        assert_eq!(parse_date_time("20261019T000060Z"), Some(1_792_368_060));
+        for text in [
+            "20261019T256100",
+            "20261019T126000",
+            "20261019T120061Z",
+            "20261019",
+        ] {
+            assert_eq!(parse_date_time(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::clock::Date;
+#[cfg(unix)]
+use crate::control::{self, ControlCommand};
+use crate::export::{export_sessions, parse_optional_date, ExportFormat};
+use crate::report::{self, WeeklyReport};
+use crate::storage;
+use crate::tasks::SessionFilter;
+
+const USAGE: &str = "Usage:
+  pomodoro_timer                      start the timer
+  pomodoro_timer export FORMAT [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--output FILE]
+                                      export the session history as csv, json or ics
+  pomodoro_timer extend MINUTES       add minutes to the phase running in the open app
+  pomodoro_timer report [--week YYYY-MM-DD] [--project NAME] [--tag NAME] [--html]
+                        [--output FILE]
+                                      weekly report as markdown (or html) for the week
+                                      containing the date, this week by default";
+
+//Runs the command given on the command line instead of the gui.
+pub fn run_command(args: &[String]) -> Result<(), String> {
+    match args.first().map(|arg| arg.as_str()) {
+        Some("export") => export(&args[1..]),
+        Some("extend") => extend(&args[1..]),
+        Some("report") => weekly_report(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+fn export(args: &[String]) -> Result<(), String> {
+    let format = args
+        .first()
+        .and_then(|format| ExportFormat::parse(format))
+        .ok_or_else(|| String::from(USAGE))?;
+    let from = parse_optional_date(&option_value(args, "--from")?.unwrap_or_default())?;
+    let to = parse_optional_date(&option_value(args, "--to")?.unwrap_or_default())?;
+    let history = storage::load_saved_history()?;
+    let text = export_sessions(&history.in_date_range(from, to), format);
+    match option_value(args, "--output")? {
+        Some(path) => {
+            std::fs::write(&path, text).map_err(|err| format!("Could not write {}: {}", path, err))
+        }
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+//Sends the command to the running app over its control socket.
+#[cfg(unix)]
+fn extend(args: &[String]) -> Result<(), String> {
+    let command = ControlCommand::parse(&format!("extend {}", args.join(" ")))?;
+    println!("{}", control::send_command(command)?);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn extend(_args: &[String]) -> Result<(), String> {
+    Err(String::from(
+        "The app can only be controlled from the command line on unix systems",
+    ))
+}
+
+fn weekly_report(args: &[String]) -> Result<(), String> {
+    let week_start = match option_value(args, "--week")? {
+        Some(date) => report::week_start(
+            Date::parse(&date)
+                .ok_or_else(|| format!("\"{}\" is not a date of the form YYYY-MM-DD", date))?,
+        ),
+        None => report::this_week(),
+    };
+    let history = storage::load_saved_history()?;
+    let filter = SessionFilter {
+        project: option_value(args, "--project")?,
+        tag: option_value(args, "--tag")?,
+    };
+    let report = WeeklyReport::new(&history, week_start, &filter);
+    let text = if args.iter().any(|arg| arg == "--html") {
+        report.to_html()
+    } else {
+        report.to_markdown()
+    };
+    match option_value(args, "--output")? {
+        Some(path) => {
+            std::fs::write(&path, text).map_err(|err| format!("Could not write {}: {}", path, err))
+        }
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+//Value following a flag such as "--from 2026-01-01".
+pub fn option_value(args: &[String], flag: &str) -> Result<Option<String>, String> {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => args
+            .get(index + 1)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("{} needs a value", flag)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,258 @@
+//Wall clock time as seconds since the unix epoch and conversion to local calendar dates.
+//std::time::SystemTime panics on the web, so there we ask javascript instead.
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+//Indexed by Date::weekday.
+pub const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|dur| dur.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now_unix() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+//Seconds east of UTC of the local time zone at the given time (this changes with daylight saving).
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+pub fn utc_offset_at(unix: u64) -> i64 {
+    let time = unix as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::localtime_r(&time, &mut tm) };
+    if result.is_null() {
+        0
+    } else {
+        //tm_gmtoff is a c_long, which is not 64 bits on every platform.
+        #[allow(clippy::useless_conversion)]
+        i64::from(tm.tm_gmtoff)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn utc_offset_at(unix: u64) -> i64 {
+    let date = js_sys::Date::new(&eframe::wasm_bindgen::JsValue::from_f64(
+        unix as f64 * 1000.0,
+    ));
+    -(date.get_timezone_offset() as i64) * 60
+}
+
+//Without libc we have no portable way to find the time zone, so times are shown in UTC.
+#[cfg(not(any(unix, target_arch = "wasm32")))]
+pub fn utc_offset_at(_unix: u64) -> i64 {
+    0
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct DateTime {
+    pub date: Date,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl Date {
+    //Converts days since 1970-01-01 into a date, see
+    //http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    pub fn from_days(days: i64) -> Date {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    //Days since 1970-01-01, the inverse of `from_days`.
+    pub fn to_days(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (i64::from(self.month) + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    //Parses dates of the form "YYYY-MM-DD".
+    pub fn parse(text: &str) -> Option<Date> {
+        let mut parts = text.trim().split('-');
+        let year = parts.next()?.parse::<i32>().ok()?;
+        let month = parts.next()?.parse::<u32>().ok()?;
+        let day = parts.next()?.parse::<u32>().ok()?;
+        if parts.next().is_some() || !(1..=12).contains(&month) || day == 0 {
+            return None;
+        }
+        let date = Date { year, month, day };
+        //Days past the end of the month would silently roll over into the next one.
+        if Date::from_days(date.to_days()) != date {
+            return None;
+        }
+        Some(date)
+    }
+
+    //0 is monday and 6 is sunday, 1970-01-01 was a thursday.
+    pub fn weekday(&self) -> u32 {
+        (self.to_days() + 3).rem_euclid(7) as u32
+    }
+
+    pub fn add_days(&self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl DateTime {
+    fn from_secs(secs: i64) -> DateTime {
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY) as u32;
+        DateTime {
+            date: Date::from_days(secs.div_euclid(SECS_PER_DAY)),
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+        }
+    }
+
+    pub fn local(unix: u64) -> DateTime {
+        DateTime::from_secs(unix as i64 + utc_offset_at(unix))
+    }
+
+    pub fn utc(unix: u64) -> DateTime {
+        DateTime::from_secs(unix as i64)
+    }
+
+    pub fn seconds_of_day(&self) -> u32 {
+        self.hour * 3600 + self.minute * 60 + self.second
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:02}:{:02}", self.date, self.hour, self.minute)
+    }
+}
+
+//Unix time of a local wall clock time. The offset is looked up twice so that dates on the other
+//side of a daylight saving change get the right one.
+pub fn local_to_unix(date: Date, seconds_of_day: u32) -> u64 {
+    let local = date.to_days() * SECS_PER_DAY + i64::from(seconds_of_day);
+    let guess = (local - utc_offset_at(local.max(0) as u64)).max(0);
+    (local - utc_offset_at(guess as u64)).max(0) as u64
+}
+
+//"2026-10-19T09:30:00+02:00"
+pub fn format_rfc3339_local(unix: u64) -> String {
+    let offset = utc_offset_at(unix);
+    let local = DateTime::local(unix);
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!(
+        "{}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        local.date,
+        local.hour,
+        local.minute,
+        local.second,
+        sign,
+        offset.abs() / 3600,
+        offset.abs() / 60 % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn converts_days_both_ways() {
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(date(2000, 3, 1).to_days() - date(2000, 2, 28).to_days(), 2);
+        assert_eq!(date(1900, 3, 1).to_days() - date(1900, 2, 28).to_days(), 1);
+        for days in (-800_000..800_000).step_by(997) {
+            assert_eq!(Date::from_days(days).to_days(), days);
+        }
+        assert_eq!(date(2026, 12, 30).add_days(3), date(2027, 1, 2));
+        assert_eq!(date(2024, 3, 1).add_days(-1), date(2024, 2, 29));
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(Date::parse(" 2024-02-29 "), Some(date(2024, 2, 29)));
+        assert_eq!(Date::parse("2024-2-9"), Some(date(2024, 2, 9)));
+        assert_eq!(
+            Date::parse(&date(2026, 10, 19).to_string()),
+            Some(date(2026, 10, 19))
+        );
+        for text in [
+            "",
+            "2024",
+            "2024-01",
+            "2024-01-01-01",
+            "2024-00-10",
+            "2024-13-01",
+            "2024-01-00",
+            "2024-04-31",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-01-xx",
+            "2024/01/01",
+            "-1-01-01",
+        ] {
+            assert_eq!(Date::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn finds_weekdays() {
+        assert_eq!(DAY_NAMES[date(1970, 1, 1).weekday() as usize], "Thu");
+        assert_eq!(DAY_NAMES[date(2026, 10, 19).weekday() as usize], "Mon");
+        assert_eq!(DAY_NAMES[date(1969, 12, 28).weekday() as usize], "Sun");
+    }
+
+    #[test]
+    fn splits_unix_time() {
+        let time = DateTime::utc(1_700_000_000);
+        assert_eq!(time.date, date(2023, 11, 14));
+        assert_eq!((time.hour, time.minute, time.second), (22, 13, 20));
+        assert_eq!(time.seconds_of_day(), 22 * 3600 + 13 * 60 + 20);
+        assert_eq!(time.to_string(), "2023-11-14 22:13");
+    }
+
+    #[test]
+    fn local_times_round_trip() {
+        for day in [date(2024, 1, 15), date(2024, 3, 31), date(2024, 7, 1)] {
+            let unix = local_to_unix(day, 12 * 3600 + 30 * 60);
+            let local = DateTime::local(unix);
+            assert_eq!(local.date, day);
+            assert_eq!(local.seconds_of_day(), 12 * 3600 + 30 * 60);
+        }
+        let unix = 1_700_000_000;
+        let text = format_rfc3339_local(unix);
+        assert_eq!(text.len(), "2023-11-14T22:13:20+00:00".len());
+        let local = DateTime::local(unix);
+        assert!(text.starts_with(&format!("{}T{:02}", local.date, local.hour)));
+    }
+}
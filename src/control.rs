@@ -0,0 +1,161 @@
+//Control socket through which other programs, e.g. `pomodoro_timer extend 5`, can drive the
+//running app. It is a unix domain socket next to the saved app state, a command is a single line
+//and so is the reply.
+use crate::storage;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+//How long a client waits for the app to answer, the app answers on its next frame.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlCommand {
+    //Adds this many minutes to the running or paused phase.
+    Extend(u64),
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Result<ControlCommand, String> {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("extend"), Some(minutes), None) => minutes
+                .parse::<u64>()
+                .ok()
+                .filter(|minutes| (1..=24 * 60).contains(minutes))
+                .map(ControlCommand::Extend)
+                .ok_or_else(|| format!("\"{}\" is not a number of minutes", minutes)),
+            _ => Err(format!("Unknown command \"{}\"", line.trim())),
+        }
+    }
+}
+
+impl std::fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlCommand::Extend(minutes) => write!(f, "extend {}", minutes),
+        }
+    }
+}
+
+//A command together with where the app should send its reply.
+pub type ControlRequest = (ControlCommand, Sender<Result<String, String>>);
+
+pub struct ControlSocket {
+    path: PathBuf,
+    receiver: Receiver<ControlRequest>,
+}
+
+impl ControlSocket {
+    //None when the socket cannot be created or another instance of the app already listens.
+    pub fn listen(ctx: egui::Context) -> Option<ControlSocket> {
+        let path = storage::control_socket_path()?;
+        if UnixStream::connect(&path).is_ok() {
+            return None;
+        }
+        //Left behind by a run that did not exit cleanly.
+        let _ = std::fs::remove_file(&path);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).ok()?;
+        }
+        let listener = UnixListener::bind(&path).ok()?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            //A client that goes away early does not stop the socket.
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &sender, &ctx);
+            }
+        });
+        Some(ControlSocket { path, receiver })
+    }
+
+    pub fn try_recv(&self) -> Option<ControlRequest> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+//Reads one command, hands it to the app and writes back its reply.
+fn serve(
+    stream: UnixStream,
+    sender: &Sender<ControlRequest>,
+    ctx: &egui::Context,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).take(1024).read_line(&mut line)?;
+    let reply = match ControlCommand::parse(&line) {
+        Ok(command) => {
+            let (reply_sender, reply_receiver) = mpsc::channel();
+            sender
+                .send((command, reply_sender))
+                .map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+            ctx.request_repaint();
+            reply_receiver
+                .recv_timeout(REPLY_TIMEOUT)
+                .unwrap_or_else(|_| Err(String::from("The app did not answer")))
+        }
+        Err(err) => Err(err),
+    };
+    let line = match reply {
+        Ok(message) => format!("ok {}\n", message),
+        Err(message) => format!("error {}\n", message),
+    };
+    (&stream).write_all(line.as_bytes())
+}
+
+//Sends a command to the running app and returns its reply.
+pub fn send_command(command: ControlCommand) -> Result<String, String> {
+    let path = storage::control_socket_path().ok_or("Could not find the app's data directory")?;
+    let mut stream =
+        UnixStream::connect(path).map_err(|_| String::from("The timer app is not running"))?;
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT * 2))
+        .map_err(|err| err.to_string())?;
+    writeln!(stream, "{}", command).map_err(|err| err.to_string())?;
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|err| format!("No reply from the app: {}", err))?;
+    match reply.trim_end().split_once(' ') {
+        Some(("ok", message)) => Ok(message.to_owned()),
+        Some(("error", message)) => Err(message.to_owned()),
+        _ => Err(format!("Unexpected reply \"{}\"", reply.trim_end())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            ControlCommand::parse("extend 5\n"),
+            Ok(ControlCommand::Extend(5))
+        );
+        assert_eq!(
+            ControlCommand::parse(&ControlCommand::Extend(12).to_string()),
+            Ok(ControlCommand::Extend(12))
+        );
+        for line in [
+            "",
+            "extend",
+            "extend 0",
+            "extend -1",
+            "extend five",
+            "extend 1 2",
+            "stop",
+        ] {
+            assert!(ControlCommand::parse(line).is_err(), "{}", line);
+        }
+    }
+}
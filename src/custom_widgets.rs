@@ -1,26 +1,43 @@
 use eframe::egui::{Color32, RichText};
 use egui::Rounding;
+use egui::{FontFamily, FontId, Vec2};
 use egui::{Margin, Response};
-use egui::{TextStyle, Ui, Widget};
+use egui::{Ui, Widget};
 
 pub struct TimerDisplay {
     timer_bg_color: Color32,
     border_color: Color32,
     timer_text: String,
+    size: Vec2,
+    //Color the background alternates with while flashing.
+    flash_color: Option<Color32>,
 }
 impl Widget for TimerDisplay {
     fn ui(self, ui: &mut Ui) -> Response {
+        let time = ui.input(|i| i.time);
+        let fill = match self.flash_color {
+            Some(flash_color) if (time * 2.0) as i64 % 2 == 0 => flash_color,
+            _ => self.timer_bg_color,
+        };
+        if self.flash_color.is_some() {
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(250));
+        }
         let inner_respons = egui::Frame::none()
-            .fill(self.timer_bg_color)
+            .fill(fill)
             .inner_margin(Margin::same(0.0))
             .outer_margin(Margin::same(0.0))
             .rounding(Rounding::same(5.0))
             .stroke(egui::Stroke::new(2.0, self.border_color))
             .show(ui, |ui| {
+                //The text has to fit both the height and the width ("XX:XX" is roughly four
+                //times as wide as it is tall), so the font size follows the tighter one.
+                let font_size = (self.size.y * 0.8).min(self.size.x * 0.24);
                 ui.add_sized(
-                    [100.0, 30.0],
+                    self.size,
                     egui::Label::new(
-                        RichText::new(self.timer_text).text_style(TextStyle::Name("Timer".into())),
+                        RichText::new(self.timer_text)
+                            .font(FontId::new(font_size, FontFamily::Monospace)),
                     ),
                 )
             });
@@ -28,11 +45,23 @@ impl Widget for TimerDisplay {
     }
 }
 impl TimerDisplay {
-    pub fn new(timer_bg_color: Color32, border_color: Color32, timer_text: String) -> TimerDisplay {
+    pub fn new(
+        timer_bg_color: Color32,
+        border_color: Color32,
+        timer_text: String,
+        size: Vec2,
+    ) -> TimerDisplay {
         TimerDisplay {
             timer_bg_color,
             timer_text,
             border_color,
+            size,
+            flash_color: None,
         }
     }
+    //Makes the background blink between its color and `flash_color`.
+    pub fn flashing(mut self, flash_color: Color32) -> TimerDisplay {
+        self.flash_color = Some(flash_color);
+        self
+    }
 }
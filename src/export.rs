@@ -0,0 +1,382 @@
+use crate::clock::{self, Date, DateTime};
+use crate::session::{SessionHistory, SessionRecord};
+use crate::timer::WorkTimes;
+use egui::Ui;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    ICalendar,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::ICalendar => "ics",
+        }
+    }
+    pub fn parse(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "ics" | "ical" | "icalendar" => Some(ExportFormat::ICalendar),
+            _ => None,
+        }
+    }
+}
+
+pub fn export_sessions(sessions: &[&SessionRecord], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => to_csv(sessions),
+        ExportFormat::Json => to_json(sessions),
+        ExportFormat::ICalendar => to_icalendar(sessions, clock::now_unix()),
+    }
+}
+
+fn to_csv(sessions: &[&SessionRecord]) -> String {
+    let mut csv = String::from("phase,task,start,end,duration_seconds,completed\n");
+    for session in sessions {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&session.work_time.to_string()),
+            csv_field(session.task.as_deref().unwrap_or("")),
+            clock::format_rfc3339_local(session.start),
+            clock::format_rfc3339_local(session.end),
+            session.duration.as_secs(),
+            session.completed
+        ));
+    }
+    csv
+}
+
+//Quotes a field if it contains anything that would break the row apart.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn to_json(sessions: &[&SessionRecord]) -> String {
+    let entries: Vec<String> = sessions
+        .iter()
+        .map(|session| {
+            let task = match &session.task {
+                Some(task) => json_string(task),
+                None => String::from("null"),
+            };
+            format!(
+                "  {{\"phase\": {}, \"task\": {}, \"start\": {}, \"end\": {}, \"duration_seconds\": {}, \"completed\": {}}}",
+                json_string(&session.work_time.to_string()),
+                task,
+                json_string(&clock::format_rfc3339_local(session.start)),
+                json_string(&clock::format_rfc3339_local(session.end)),
+                session.duration.as_secs(),
+                session.completed
+            )
+        })
+        .collect();
+    if entries.is_empty() {
+        String::from("[]\n")
+    } else {
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+//Every work session becomes an event, breaks are left out of the calendar.
+fn to_icalendar(sessions: &[&SessionRecord], now: u64) -> String {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//pomodoro_timer//EN"),
+    ];
+    for session in sessions
+        .iter()
+        .filter(|session| session.work_time == WorkTimes::Work)
+    {
+        let summary = session.task.as_deref().unwrap_or("Pomodoro");
+        let status = if session.completed {
+            "completed"
+        } else {
+            "stopped early"
+        };
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!(
+            "UID:{}-{}@pomodoro_timer",
+            session.start, session.end
+        ));
+        lines.push(format!("DTSTAMP:{}", ical_time(now)));
+        lines.push(format!("DTSTART:{}", ical_time(session.start)));
+        lines.push(format!("DTEND:{}", ical_time(session.end)));
+        lines.push(format!("SUMMARY:{}", ical_text(summary)));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            ical_text(&format!(
+                "{} min focus, {}",
+                session.duration.as_secs() / 60,
+                status
+            ))
+        ));
+        lines.push(String::from("END:VEVENT"));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+    lines
+        .iter()
+        .map(|line| ical_fold(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+//"20261019T073000Z"
+pub fn ical_time(unix: u64) -> String {
+    let utc = DateTime::utc(unix);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        utc.date.year, utc.date.month, utc.date.day, utc.hour, utc.minute, utc.second
+    )
+}
+
+pub fn ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+//Content lines longer than 75 bytes have to be folded onto continuation lines starting with a space.
+fn ical_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+//State of the export window on the history screen.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExportDialog {
+    format: ExportFormat,
+    from: String,
+    to: String,
+    path: String,
+    status: Option<String>,
+}
+
+impl Default for ExportDialog {
+    fn default() -> Self {
+        ExportDialog {
+            format: ExportFormat::Csv,
+            from: String::new(),
+            to: String::new(),
+            path: String::from("pomodoro_sessions.csv"),
+            status: None,
+        }
+    }
+}
+
+impl ExportDialog {
+    //Returns false once the dialog should be closed.
+    pub fn show(&mut self, ctx: &egui::Context, history: &SessionHistory) -> bool {
+        let mut open = true;
+        egui::Window::new("Export")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| self.draw(ui, history));
+        open
+    }
+
+    fn draw(&mut self, ui: &mut Ui, history: &SessionHistory) {
+        ui.horizontal(|ui| {
+            for format in [
+                ExportFormat::Csv,
+                ExportFormat::Json,
+                ExportFormat::ICalendar,
+            ] {
+                if ui
+                    .radio(self.format == format, format.extension())
+                    .clicked()
+                {
+                    self.format = format;
+                    self.path = match self.path.rsplit_once('.') {
+                        Some((stem, _)) => format!("{}.{}", stem, format.extension()),
+                        None => format!("{}.{}", self.path, format.extension()),
+                    };
+                }
+            }
+        });
+        ui.add(egui::TextEdit::singleline(&mut self.from).hint_text("From YYYY-MM-DD"));
+        ui.add(egui::TextEdit::singleline(&mut self.to).hint_text("To YYYY-MM-DD"));
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.add(egui::TextEdit::singleline(&mut self.path).hint_text("File"));
+        ui.horizontal(|ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Save").clicked() {
+                self.status = Some(match self.exported_text(history) {
+                    Ok(text) => match std::fs::write(&self.path, text) {
+                        Ok(()) => format!("Saved to {}", self.path),
+                        Err(err) => format!("Could not save: {}", err),
+                    },
+                    Err(err) => err,
+                });
+            }
+            if ui.button("Copy").clicked() {
+                self.status = Some(match self.exported_text(history) {
+                    Ok(text) => {
+                        ui.output_mut(|o| o.copied_text = text);
+                        String::from("Copied to clipboard")
+                    }
+                    Err(err) => err,
+                });
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+    }
+
+    fn exported_text(&self, history: &SessionHistory) -> Result<String, String> {
+        let from = parse_optional_date(&self.from)?;
+        let to = parse_optional_date(&self.to)?;
+        Ok(export_sessions(
+            &history.in_date_range(from, to),
+            self.format,
+        ))
+    }
+}
+
+//An empty field means no limit.
+pub fn parse_optional_date(text: &str) -> Result<Option<Date>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    Date::parse(text)
+        .map(Some)
+        .ok_or_else(|| format!("\"{}\" is not a date of the form YYYY-MM-DD", text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{parse_table, preview_import, ColumnMapping};
+    use std::time::Duration;
+
+    fn sessions() -> Vec<SessionRecord> {
+        let work = SessionRecord {
+            end: 1_700_001_500,
+            duration: Duration::from_secs(1500),
+            completed: true,
+            ..SessionRecord::new(
+                WorkTimes::Work,
+                Some(String::from("Write \"report\", part 1\nthen review\\ö")),
+                1_700_000_000,
+            )
+        };
+        let short = SessionRecord {
+            end: 1_700_001_800,
+            duration: Duration::from_secs(240),
+            ..SessionRecord::new(WorkTimes::Short, None, 1_700_001_500)
+        };
+        let long = SessionRecord {
+            end: 1_700_003_000,
+            duration: Duration::from_secs(900),
+            completed: true,
+            ..SessionRecord::new(WorkTimes::Long, Some(String::from("a,b")), 1_700_002_100)
+        };
+        vec![work, short, long]
+    }
+
+    fn round_trip(format: ExportFormat) {
+        let sessions = sessions();
+        let refs: Vec<&SessionRecord> = sessions.iter().collect();
+        let table = parse_table(&export_sessions(&refs, format)).unwrap();
+        let mapping = ColumnMapping::detect(&table.headers);
+        let preview = preview_import(&table, &mapping, &SessionHistory::default());
+        assert_eq!(preview.errors, Vec::<String>::new());
+        assert_eq!(preview.new_sessions, sessions, "{:?}", format);
+    }
+
+    #[test]
+    fn csv_export_imports_again() {
+        round_trip(ExportFormat::Csv);
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn json_export_imports_again() {
+        round_trip(ExportFormat::Json);
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+        let empty: Vec<&SessionRecord> = Vec::new();
+        assert_eq!(export_sessions(&empty, ExportFormat::Json), "[]\n");
+    }
+
+    #[test]
+    fn icalendar_lists_work_sessions() {
+        let sessions = sessions();
+        let refs: Vec<&SessionRecord> = sessions.iter().collect();
+        let ics = to_icalendar(&refs, 1_700_010_000);
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        let unfolded = ics.replace("\r\n ", "");
+        assert_eq!(unfolded.matches("BEGIN:VEVENT").count(), 1);
+        assert!(unfolded.contains("\r\nDTSTART:20231114T221320Z\r\n"));
+        assert!(unfolded.contains("\r\nDTEND:20231114T223820Z\r\n"));
+        assert!(unfolded.contains("\r\nSUMMARY:Write \"report\"\\, part 1\\nthen review\\\\ö\r\n"));
+        assert!(unfolded.contains("\r\nDESCRIPTION:25 min focus\\, completed\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_on_characters() {
+        let line = "ö".repeat(100);
+        let folded = ical_fold(&line);
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn parses_formats_and_dates() {
+        assert_eq!(ExportFormat::parse("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("ical"), Some(ExportFormat::ICalendar));
+        assert_eq!(ExportFormat::parse("xml"), None);
+        assert_eq!(parse_optional_date("  "), Ok(None));
+        assert_eq!(
+            parse_optional_date("2026-10-19"),
+            Ok(Some(Date {
+                year: 2026,
+                month: 10,
+                day: 19
+            }))
+        );
+        assert!(parse_optional_date("2026-10-32").is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::clock::{Date, DateTime};
+use crate::session::SessionHistory;
+use crate::timer::WorkTimes;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum GoalUnit {
+    Pomodoros,
+    FocusMinutes,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DailyGoal {
+    //0 turns the goal off.
+    pub target: u32,
+    pub unit: GoalUnit,
+    //Hour at which a new day starts, so that working past midnight still counts for the evening.
+    pub day_start_hour: u32,
+}
+
+impl Default for DailyGoal {
+    fn default() -> Self {
+        DailyGoal {
+            target: 8,
+            unit: GoalUnit::Pomodoros,
+            day_start_hour: 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct GoalProgress {
+    pub today: u32,
+    pub target: u32,
+    pub streak: u32,
+}
+
+//The progress shown every frame, worked out again only when the goal, the focus day or the number
+//of sessions changes.
+#[derive(Default)]
+pub struct ProgressCache {
+    key: Option<(DailyGoal, Date, usize)>,
+    progress: GoalProgress,
+}
+
+impl ProgressCache {
+    pub fn progress(
+        &mut self,
+        goal: &DailyGoal,
+        history: &SessionHistory,
+        now: u64,
+    ) -> GoalProgress {
+        let key = (*goal, goal.focus_day(now), history.sessions().len());
+        if self.key != Some(key) {
+            self.progress = goal.progress(history, now);
+            self.key = Some(key);
+        }
+        self.progress
+    }
+}
+
+impl DailyGoal {
+    pub fn is_enabled(&self) -> bool {
+        self.target > 0
+    }
+
+    //The day a moment counts towards, which is the local date shifted back by the day start.
+    pub fn focus_day(&self, unix: u64) -> Date {
+        DateTime::local(unix.saturating_sub(u64::from(self.day_start_hour) * 3600)).date
+    }
+
+    //Completed pomodoros or minutes of work per focus day.
+    pub fn totals_per_day(&self, history: &SessionHistory) -> HashMap<Date, u32> {
+        let mut seconds_per_day: HashMap<Date, u64> = HashMap::new();
+        let mut pomodoros_per_day: HashMap<Date, u32> = HashMap::new();
+        for session in history
+            .sessions()
+            .iter()
+            .filter(|session| session.work_time == WorkTimes::Work)
+        {
+            let day = self.focus_day(session.start);
+            *seconds_per_day.entry(day).or_default() += session.duration.as_secs();
+            if session.completed {
+                *pomodoros_per_day.entry(day).or_default() += 1;
+            }
+        }
+        match self.unit {
+            GoalUnit::Pomodoros => pomodoros_per_day,
+            GoalUnit::FocusMinutes => seconds_per_day
+                .into_iter()
+                .map(|(day, seconds)| (day, (seconds / 60) as u32))
+                .collect(),
+        }
+    }
+
+    //Today's progress and the number of days in a row the goal was met. Today only breaks the
+    //streak once it is over, until then the streak counts up to yesterday.
+    pub fn progress(&self, history: &SessionHistory, now: u64) -> GoalProgress {
+        let totals = self.totals_per_day(history);
+        let met = |day: &Date| totals.get(day).map_or(false, |total| *total >= self.target);
+        let today = self.focus_day(now);
+        let mut day = if met(&today) {
+            today
+        } else {
+            today.add_days(-1)
+        };
+        let mut streak = 0;
+        while met(&day) {
+            streak += 1;
+            day = day.add_days(-1);
+        }
+        GoalProgress {
+            today: totals.get(&today).copied().unwrap_or(0),
+            target: self.target,
+            streak,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionRecord;
+    use crate::test_util::{local_time, work_session, DAY as TODAY};
+
+    fn session(day: i64, hour: u32, minutes: u64, completed: bool) -> SessionRecord {
+        SessionRecord {
+            completed,
+            ..work_session(day, hour, minutes)
+        }
+    }
+
+    fn history(sessions: Vec<SessionRecord>) -> SessionHistory {
+        let mut history = SessionHistory::default();
+        history.import(sessions);
+        history
+    }
+
+    fn goal(target: u32, unit: GoalUnit) -> DailyGoal {
+        DailyGoal {
+            target,
+            unit,
+            day_start_hour: 4,
+        }
+    }
+
+    #[test]
+    fn counts_the_streak_up_to_yesterday() {
+        let now = local_time(0, 15);
+        let mut sessions = vec![
+            session(-4, 10, 25, true),
+            session(-4, 11, 25, true),
+            session(-2, 10, 25, true),
+            session(-2, 11, 25, true),
+            session(-1, 10, 25, true),
+            session(-1, 11, 25, true),
+            session(0, 10, 25, true),
+        ];
+        let goal = goal(2, GoalUnit::Pomodoros);
+        let progress = goal.progress(&history(sessions.clone()), now);
+        assert_eq!(
+            progress,
+            GoalProgress {
+                today: 1,
+                target: 2,
+                streak: 2
+            }
+        );
+        //Meeting today's goal extends the streak, a missed day (-3) ends it.
+        sessions.push(session(0, 11, 25, true));
+        assert_eq!(goal.progress(&history(sessions), now).streak, 3);
+        assert_eq!(
+            goal.progress(&SessionHistory::default(), now),
+            GoalProgress {
+                today: 0,
+                target: 2,
+                streak: 0
+            }
+        );
+    }
+
+    #[test]
+    fn counts_late_work_for_the_evening_before() {
+        let goal = goal(1, GoalUnit::Pomodoros);
+        let late = session(0, 2, 25, true);
+        assert_eq!(goal.focus_day(late.start), TODAY.add_days(-1));
+        assert_eq!(goal.focus_day(session(0, 5, 25, true).start), TODAY);
+        let totals = goal.totals_per_day(&history(vec![late]));
+        assert_eq!(totals.get(&TODAY.add_days(-1)), Some(&1));
+        assert_eq!(totals.get(&TODAY), None);
+    }
+
+    #[test]
+    fn counts_focus_minutes_of_work_only() {
+        let mut short_break = session(0, 12, 5, true);
+        short_break.work_time = WorkTimes::Short;
+        let sessions = vec![
+            session(0, 10, 25, true),
+            session(0, 11, 10, false),
+            short_break,
+        ];
+        let history = history(sessions);
+        let minutes = goal(30, GoalUnit::FocusMinutes).totals_per_day(&history);
+        assert_eq!(minutes.get(&TODAY), Some(&35));
+        let pomodoros = goal(1, GoalUnit::Pomodoros).totals_per_day(&history);
+        assert_eq!(pomodoros.get(&TODAY), Some(&1));
+        assert!(!goal(0, GoalUnit::Pomodoros).is_enabled());
+    }
+
+    #[test]
+    fn caches_progress_until_something_changes() {
+        let mut history = history(vec![session(0, 10, 25, true)]);
+        let goal = goal(2, GoalUnit::Pomodoros);
+        let mut cache = ProgressCache::default();
+        let morning = local_time(0, 9);
+        assert_eq!(cache.progress(&goal, &history, morning).today, 1);
+        history.push(session(0, 11, 25, true));
+        assert_eq!(cache.progress(&goal, &history, morning).today, 2);
+        let tomorrow = local_time(1, 9);
+        assert_eq!(cache.progress(&goal, &history, tomorrow).today, 0);
+        assert_eq!(cache.progress(&goal, &history, tomorrow).streak, 1);
+    }
+}
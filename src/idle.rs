@@ -0,0 +1,168 @@
+use crate::clock;
+use std::time::Duration;
+
+//Keeps track of how long the user has been away. By default only input to the app itself counts
+//as activity, and only while its window has focus, since input to other apps cannot be seen. On
+//Linux the X11 screensaver extension can be asked for the system wide idle time.
+pub struct IdleDetector {
+    last_activity: f64,
+    //Opened the first time the system idle time is asked for, None inside if that failed.
+    #[cfg(target_os = "linux")]
+    system_idle: Option<Option<x11::SystemIdle>>,
+    pending: Option<PendingIdle>,
+}
+
+//An idle stretch that auto-paused the timer and that the user has not yet decided to keep or
+//discard. `counted` is the part of it that had already been counted down on the timer.
+#[derive(Clone, Copy)]
+pub struct PendingIdle {
+    pub start: u64,
+    pub counted: Duration,
+}
+
+impl Default for IdleDetector {
+    fn default() -> Self {
+        IdleDetector {
+            last_activity: 0.0,
+            #[cfg(target_os = "linux")]
+            system_idle: None,
+            pending: None,
+        }
+    }
+}
+
+impl IdleDetector {
+    pub fn pending(&self) -> Option<PendingIdle> {
+        self.pending
+    }
+    pub fn take_pending(&mut self) -> Option<PendingIdle> {
+        self.pending.take()
+    }
+    //Remembers that the timer was auto-paused after being idle for `idle_for`.
+    pub fn start_pending(&mut self, idle_for: Duration, counted: Duration) {
+        self.pending = Some(PendingIdle {
+            start: clock::now_unix().saturating_sub(idle_for.as_secs()),
+            counted,
+        });
+    }
+
+    //Should be called every frame, returns for how long there has been no activity.
+    pub fn idle_time(&mut self, ctx: &egui::Context, use_system_idle: bool) -> Duration {
+        let (now, had_input, focused) = ctx.input(|i| (i.time, !i.events.is_empty(), i.focused));
+        //Someone working in another window is not idle.
+        if had_input || !focused {
+            self.last_activity = now;
+        }
+        if use_system_idle {
+            if let Some(system_idle) = self.system_idle_time() {
+                return system_idle;
+            }
+        }
+        Duration::from_secs_f64((now - self.last_activity).max(0.0))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn system_idle_time(&mut self) -> Option<Duration> {
+        self.system_idle
+            .get_or_insert_with(x11::SystemIdle::open)
+            .as_ref()
+            .and_then(|idle| idle.query())
+    }
+
+    //There is no system wide idle time outside of X11 (Wayland has no common protocol for it
+    //yet), so we fall back on the input to the app.
+    #[cfg(not(target_os = "linux"))]
+    fn system_idle_time(&mut self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::ffi::OsString;
+    use std::ptr;
+    use std::time::Duration;
+    use x11_dl::xlib::{Display, Xlib};
+    use x11_dl::xss::Xss;
+
+    //Connection to the X server used to ask the screensaver extension for the idle time. Both
+    //libraries are loaded at runtime so the app still starts where they are missing.
+    pub struct SystemIdle {
+        xlib: Xlib,
+        xss: Xss,
+        display: *mut Display,
+    }
+
+    impl SystemIdle {
+        pub fn open() -> Option<SystemIdle> {
+            //Under XWayland the X server only sees input to X11 windows, so the user would look
+            //idle while typing into a Wayland window.
+            if is_wayland(|name| std::env::var_os(name)) {
+                return None;
+            }
+            let xlib = Xlib::open().ok()?;
+            let xss = Xss::open().ok()?;
+            let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+            if display.is_null() {
+                return None;
+            }
+            Some(SystemIdle { xlib, xss, display })
+        }
+
+        pub fn query(&self) -> Option<Duration> {
+            unsafe {
+                let info = (self.xss.XScreenSaverAllocInfo)();
+                if info.is_null() {
+                    return None;
+                }
+                let root = (self.xlib.XDefaultRootWindow)(self.display);
+                let status = (self.xss.XScreenSaverQueryInfo)(self.display, root, info);
+                let idle = (*info).idle;
+                (self.xlib.XFree)(info.cast());
+                if status == 0 {
+                    None
+                } else {
+                    //c_ulong is not 64 bits on every platform.
+                    #[allow(clippy::useless_conversion)]
+                    Some(Duration::from_millis(u64::from(idle)))
+                }
+            }
+        }
+    }
+
+    //A Wayland session, going by the variables the compositor and the login manager set.
+    pub fn is_wayland(var: impl Fn(&str) -> Option<OsString>) -> bool {
+        var("WAYLAND_DISPLAY").map_or(false, |display| !display.is_empty())
+            || var("XDG_SESSION_TYPE").map_or(false, |session| session == "wayland")
+    }
+
+    impl Drop for SystemIdle {
+        fn drop(&mut self) {
+            unsafe {
+                (self.xlib.XCloseDisplay)(self.display);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn skips_x11_under_wayland() {
+        use super::x11::is_wayland;
+        use std::ffi::OsString;
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert!(!is_wayland(env(&[("DISPLAY", ":0")])));
+        assert!(!is_wayland(env(&[("XDG_SESSION_TYPE", "x11")])));
+        assert!(is_wayland(env(&[("WAYLAND_DISPLAY", "wayland-0")])));
+        assert!(is_wayland(env(&[("XDG_SESSION_TYPE", "wayland")])));
+        assert!(!is_wayland(env(&[("WAYLAND_DISPLAY", "")])));
+    }
+}
@@ -0,0 +1,947 @@
+use crate::clock::{self, Date};
+use crate::session::{SessionHistory, SessionRecord};
+use crate::timer::WorkTimes;
+use egui::Ui;
+use std::time::Duration;
+
+//Rows of a CSV file or the objects of a JSON array, both are imported through the same column
+//mapping.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum DurationUnit {
+    Seconds,
+    #[default]
+    Minutes,
+}
+
+//Which column holds which part of a session, None when the file has no such column.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ColumnMapping {
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+    pub duration: Option<usize>,
+    pub task: Option<usize>,
+    pub phase: Option<usize>,
+    pub completed: Option<usize>,
+    pub duration_unit: DurationUnit,
+}
+
+//What an import would add, shown before anything is written to the history.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ImportPreview {
+    pub new_sessions: Vec<SessionRecord>,
+    pub duplicates: usize,
+    pub errors: Vec<String>,
+}
+
+//Header names used by this app's own export and by the exports of other pomodoro apps, compared
+//in lower case without spaces or punctuation. Earlier names win over later ones.
+const START_NAMES: [&str; 11] = [
+    "start",
+    "starttime",
+    "startedat",
+    "started",
+    "startdate",
+    "begin",
+    "from",
+    "createdat",
+    "timestamp",
+    "datetime",
+    "date",
+];
+const END_NAMES: [&str; 9] = [
+    "end",
+    "endtime",
+    "endedat",
+    "ended",
+    "enddate",
+    "finish",
+    "finishedat",
+    "stop",
+    "to",
+];
+const DURATION_NAMES: [&str; 11] = [
+    "durationseconds",
+    "durationsec",
+    "durationminutes",
+    "durationmin",
+    "duration",
+    "minutes",
+    "mins",
+    "seconds",
+    "length",
+    "focustime",
+    "time",
+];
+const TASK_NAMES: [&str; 7] = [
+    "task",
+    "taskname",
+    "title",
+    "name",
+    "description",
+    "activity",
+    "label",
+];
+const PHASE_NAMES: [&str; 6] = ["phase", "type", "sessiontype", "kind", "mode", "category"];
+const COMPLETED_NAMES: [&str; 4] = ["completed", "done", "finished", "status"];
+
+fn normalize_header(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+impl ColumnMapping {
+    pub fn detect(headers: &[String]) -> ColumnMapping {
+        let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+        let find = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| normalized.iter().position(|header| header == name))
+        };
+        let duration = find(&DURATION_NAMES);
+        let duration_unit = match duration.map(|index| normalized[index].as_str()) {
+            Some(header) if header.contains("sec") => DurationUnit::Seconds,
+            _ => DurationUnit::Minutes,
+        };
+        ColumnMapping {
+            start: find(&START_NAMES),
+            end: find(&END_NAMES),
+            duration,
+            task: find(&TASK_NAMES),
+            phase: find(&PHASE_NAMES),
+            completed: find(&COMPLETED_NAMES),
+            duration_unit,
+        }
+    }
+}
+
+//Reads text as JSON if it looks like JSON, otherwise as CSV.
+pub fn parse_table(text: &str) -> Result<Table, String> {
+    let trimmed = text.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        json_table(&JsonParser::new(trimmed).parse_document()?)
+    } else {
+        Ok(csv_table(trimmed))
+    }
+}
+
+pub fn csv_table(text: &str) -> Table {
+    let mut rows = parse_csv(text).into_iter();
+    let headers = rows.next().unwrap_or_default();
+    Table {
+        headers,
+        rows: rows
+            .filter(|row| row.iter().any(|field| !field.trim().is_empty()))
+            .collect(),
+    }
+}
+
+//RFC 4180 style CSV, fields may be quoted and quoted fields may contain commas, newlines and
+//doubled quotes. Semicolon separated files (common with some locales) are detected from the
+//first line.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let first_line = text.lines().next().unwrap_or("");
+    let separator = if first_line.matches(';').count() > first_line.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => (),
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+//Minimal JSON value, enough to read the exports of other apps.
+#[derive(Clone, PartialEq, Debug)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    //Scalars as the text they would have in a CSV file.
+    fn as_field(&self) -> String {
+        match self {
+            JsonValue::Null => String::new(),
+            JsonValue::Bool(value) => value.to_string(),
+            JsonValue::Number(value) => value.to_string(),
+            JsonValue::String(value) => value.clone(),
+            JsonValue::Array(_) | JsonValue::Object(_) => String::new(),
+        }
+    }
+}
+
+//Sessions are either the top level array or the first array inside the top level object, e.g.
+//`{"sessions": [...]}`.
+fn json_table(document: &JsonValue) -> Result<Table, String> {
+    let entries = match document {
+        JsonValue::Array(entries) => entries,
+        JsonValue::Object(fields) => fields
+            .iter()
+            .find_map(|(_, value)| match value {
+                JsonValue::Array(entries) => Some(entries),
+                _ => None,
+            })
+            .ok_or("The JSON file does not contain a list of sessions")?,
+        _ => {
+            return Err(String::from(
+                "The JSON file does not contain a list of sessions",
+            ))
+        }
+    };
+    let mut table = Table::default();
+    for entry in entries {
+        if let JsonValue::Object(fields) = entry {
+            for (key, _) in fields {
+                if !table.headers.contains(key) {
+                    table.headers.push(key.clone());
+                }
+            }
+        }
+    }
+    for entry in entries {
+        if let JsonValue::Object(fields) = entry {
+            let row = table
+                .headers
+                .iter()
+                .map(|header| {
+                    fields
+                        .iter()
+                        .find(|(key, _)| key == header)
+                        .map(|(_, value)| value.as_field())
+                        .unwrap_or_default()
+                })
+                .collect();
+            table.rows.push(row);
+        }
+    }
+    Ok(table)
+}
+
+pub struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> JsonParser<'a> {
+    pub fn new(text: &'a str) -> JsonParser<'a> {
+        JsonParser {
+            chars: text.chars().peekable(),
+        }
+    }
+
+    pub fn parse_document(&mut self) -> Result<JsonValue, String> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        match self.chars.next() {
+            None => Ok(value),
+            Some(c) => Err(format!(
+                "Unexpected '{}' after the end of the JSON value",
+                c
+            )),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{}' but found '{}'", expected, c)),
+            None => Err(format!("Expected '{}' but the file ended", expected)),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some('f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some('n') => self.parse_literal("null", JsonValue::Null),
+            Some(_) => self.parse_number(),
+            None => Err(String::from("Unexpected end of the JSON file")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String> {
+        for expected in literal.chars() {
+            if self.chars.next() != Some(expected) {
+                return Err(format!("Expected {}", literal));
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let mut number = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || "+-.eE".contains(c) {
+                number.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        number
+            .parse::<f64>()
+            .map(JsonValue::Number)
+            .map_err(|_| format!("\"{}\" is not a number", number))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some('r') => string.push('\r'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let code = u32::from_str_radix(&hex, 16)
+                            .map_err(|_| format!("\"\\u{}\" is not a valid escape", hex))?;
+                        string.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some(c) => string.push(c),
+                    None => return Err(String::from("Unterminated string")),
+                },
+                Some(c) => string.push(c),
+                None => return Err(String::from("Unterminated string")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some(']') => return Ok(JsonValue::Array(values)),
+                _ => return Err(String::from("Expected ',' or ']' in a list")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            fields.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some('}') => return Ok(JsonValue::Object(fields)),
+                _ => return Err(String::from("Expected ',' or '}' in an object")),
+            }
+        }
+    }
+}
+
+//Accepts unix timestamps (seconds or milliseconds), "YYYY-MM-DD", and "YYYY-MM-DD HH:MM[:SS]"
+//with a 'T' or a space in between and an optional "Z" or "+HH:MM" offset. Times without an
+//offset are taken to be local time.
+pub fn parse_timestamp(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+        let number = text.parse::<u64>().ok()?;
+        //Anything past the year 5000 in seconds is taken to be milliseconds.
+        return Some(if number > 100_000_000_000 {
+            number / 1000
+        } else {
+            number
+        });
+    }
+    let date = Date::parse(text.get(..10)?)?;
+    let rest = text[10..].trim_start_matches(['T', ' ']);
+    if rest.is_empty() {
+        return Some(clock::local_to_unix(date, 0));
+    }
+    let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(index) => (&rest[..index], Some(parse_utc_offset(&rest[index..])?)),
+        None => (rest, None),
+    };
+    let mut parts = time.trim().split(':');
+    let hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = parts.next()?.parse::<u32>().ok()?;
+    //Fractions of a second are dropped.
+    let second = match parts.next() {
+        Some(second) => second.split('.').next()?.parse::<u32>().ok()?,
+        None => 0,
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let seconds_of_day = hour * 3600 + minute * 60 + second;
+    match offset {
+        Some(offset) => {
+            let local = date.to_days() * 86400 + i64::from(seconds_of_day);
+            u64::try_from(local - offset).ok()
+        }
+        None => Some(clock::local_to_unix(date, seconds_of_day)),
+    }
+}
+
+//"Z", "+02:00", "+0200" or "-05" in seconds east of UTC.
+fn parse_utc_offset(text: &str) -> Option<i64> {
+    if text.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let sign = if text.starts_with('-') { -1 } else { 1 };
+    let digits: String = text[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i64>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i64>().ok()?,
+            digits[2..].parse::<i64>().ok()?,
+        ),
+        _ => return None,
+    };
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+//No session lasts longer than a week, longer durations are mistakes in the file.
+const MAX_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+//"25", "25.5", "1:30:00" or "25:00" (minutes and seconds).
+pub fn parse_duration(text: &str, unit: DurationUnit) -> Option<Duration> {
+    let text = text.trim();
+    let duration = if text.contains(':') {
+        let parts: Vec<u64> = text
+            .split(':')
+            .map(|part| part.trim().parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        let seconds = match parts.as_slice() {
+            [minutes, seconds] => minutes.checked_mul(60)?.checked_add(*seconds)?,
+            [hours, minutes, seconds] => hours
+                .checked_mul(3600)?
+                .checked_add(minutes.checked_mul(60)?)?
+                .checked_add(*seconds)?,
+            _ => return None,
+        };
+        Duration::from_secs(seconds)
+    } else {
+        let value = text.parse::<f64>().ok().filter(|value| *value >= 0.0)?;
+        let seconds = match unit {
+            DurationUnit::Seconds => value,
+            DurationUnit::Minutes => value * 60.0,
+        };
+        //from_secs_f64 panics on "inf" and on values too large for a Duration.
+        if !seconds.is_finite() || seconds > MAX_DURATION.as_secs_f64() {
+            return None;
+        }
+        Duration::from_secs_f64(seconds)
+    };
+    (duration <= MAX_DURATION).then_some(duration)
+}
+
+fn parse_phase(text: &str) -> WorkTimes {
+    let text = text.to_lowercase();
+    if text.contains("long") {
+        WorkTimes::Long
+    } else if text.contains("short") || text.contains("break") || text.contains("rest") {
+        WorkTimes::Short
+    } else {
+        WorkTimes::Work
+    }
+}
+
+fn parse_completed(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "completed" | "complete" | "done" | "finished" => Some(true),
+        "false" | "no" | "n" | "0" | "stopped" | "skipped" | "abandoned" | "interrupted" => {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+fn field(row: &[String], column: Option<usize>) -> Option<&str> {
+    column
+        .and_then(|column| row.get(column))
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+}
+
+//A session needs at least two of start, end and duration, the third is worked out from them.
+pub fn record_from_row(row: &[String], mapping: &ColumnMapping) -> Result<SessionRecord, String> {
+    let timestamp = |column: Option<usize>| match field(row, column) {
+        Some(text) => parse_timestamp(text)
+            .map(Some)
+            .ok_or_else(|| format!("\"{}\" is not a date and time", text)),
+        None => Ok(None),
+    };
+    let start = timestamp(mapping.start)?;
+    let end = timestamp(mapping.end)?;
+    let duration = match field(row, mapping.duration) {
+        Some(text) => Some(
+            parse_duration(text, mapping.duration_unit)
+                .ok_or_else(|| format!("\"{}\" is not a duration", text))?,
+        ),
+        None => None,
+    };
+    let (start, end, duration) = match (start, end, duration) {
+        (Some(start), Some(end), duration) if end >= start => (
+            start,
+            end,
+            duration.unwrap_or(Duration::from_secs(end - start)),
+        ),
+        (Some(_), Some(_), _) => return Err(String::from("The session ends before it starts")),
+        (Some(start), None, Some(duration)) => match start.checked_add(duration.as_secs()) {
+            Some(end) => (start, end, duration),
+            None => return Err(String::from("The session ends too far in the future")),
+        },
+        (None, Some(end), Some(duration)) => {
+            (end.saturating_sub(duration.as_secs()), end, duration)
+        }
+        _ => return Err(String::from("Needs two of start, end and duration")),
+    };
+    Ok(SessionRecord {
+        work_time: field(row, mapping.phase).map_or(WorkTimes::Work, parse_phase),
+        task: field(row, mapping.task).map(|task| task.to_owned()),
+        project: None,
+        tags: Vec::new(),
+        start,
+        end,
+        duration,
+        completed: field(row, mapping.completed)
+            .and_then(parse_completed)
+            .unwrap_or(true),
+        idle_gaps: Vec::new(),
+        extensions: Vec::new(),
+        overtime: Duration::from_secs(0),
+        interruptions: Vec::new(),
+        pauses: Vec::new(),
+        reflection: None,
+        activity: None,
+        overrides: Vec::new(),
+    })
+}
+
+//Works out what importing the table would do without touching the history.
+pub fn preview_import(
+    table: &Table,
+    mapping: &ColumnMapping,
+    history: &SessionHistory,
+) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+    for (index, row) in table.rows.iter().enumerate() {
+        match record_from_row(row, mapping) {
+            Ok(record) => {
+                let duplicate = history
+                    .sessions()
+                    .iter()
+                    .chain(preview.new_sessions.iter())
+                    .any(|existing| existing.is_duplicate_of(&record));
+                if duplicate {
+                    preview.duplicates += 1;
+                } else {
+                    preview.new_sessions.push(record);
+                }
+            }
+            //Rows are counted from the line after the header.
+            Err(err) => preview.errors.push(format!("Row {}: {}", index + 2, err)),
+        }
+    }
+    preview
+}
+
+//State of the import window on the history screen.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct ImportDialog {
+    path: String,
+    text: String,
+    table: Option<Table>,
+    mapping: ColumnMapping,
+    preview: Option<ImportPreview>,
+    status: Option<String>,
+}
+
+impl ImportDialog {
+    //Returns false once the dialog should be closed.
+    pub fn show(&mut self, ctx: &egui::Context, history: &mut SessionHistory) -> bool {
+        let mut open = true;
+        egui::Window::new("Import")
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| self.draw(ui, history));
+            });
+        open
+    }
+
+    fn draw(&mut self, ui: &mut Ui, history: &mut SessionHistory) {
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.path).hint_text("File"));
+            if ui.button("Load").clicked() {
+                match std::fs::read_to_string(self.path.trim()) {
+                    Ok(text) => {
+                        self.text = text;
+                        self.read_table();
+                    }
+                    Err(err) => self.status = Some(format!("Could not read the file: {}", err)),
+                }
+            }
+        });
+        let text_edit = ui.add(
+            egui::TextEdit::multiline(&mut self.text)
+                .hint_text("...or paste CSV/JSON here")
+                .desired_rows(3),
+        );
+        if text_edit.changed() {
+            self.read_table();
+        }
+        if let Some(table) = &self.table {
+            let headers = &table.headers;
+            let columns = [
+                ("Start", &mut self.mapping.start),
+                ("End", &mut self.mapping.end),
+                ("Duration", &mut self.mapping.duration),
+                ("Task", &mut self.mapping.task),
+                ("Phase", &mut self.mapping.phase),
+                ("Completed", &mut self.mapping.completed),
+            ];
+            let mut changed = false;
+            egui::Grid::new("import_mapping").show(ui, |ui| {
+                for (name, column) in columns {
+                    ui.label(name);
+                    let selected = column
+                        .and_then(|index| headers.get(index))
+                        .map_or("-", |header| header.as_str());
+                    egui::ComboBox::from_id_source(name)
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            changed |= ui.selectable_value(column, None, "-").changed();
+                            for (index, header) in headers.iter().enumerate() {
+                                changed |=
+                                    ui.selectable_value(column, Some(index), header).changed();
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Duration in");
+                changed |= ui
+                    .radio_value(
+                        &mut self.mapping.duration_unit,
+                        DurationUnit::Minutes,
+                        "minutes",
+                    )
+                    .changed();
+                changed |= ui
+                    .radio_value(
+                        &mut self.mapping.duration_unit,
+                        DurationUnit::Seconds,
+                        "seconds",
+                    )
+                    .changed();
+            });
+            if changed {
+                self.preview = None;
+            }
+            if ui.button("Preview").clicked() {
+                self.preview = Some(preview_import(table, &self.mapping, history));
+                self.status = None;
+            }
+        }
+        let mut import = false;
+        if let Some(preview) = &self.preview {
+            ui.label(format!(
+                "{} new sessions, {} duplicates, {} rows with errors",
+                preview.new_sessions.len(),
+                preview.duplicates,
+                preview.errors.len()
+            ));
+            for error in preview.errors.iter().take(5) {
+                ui.label(error);
+            }
+            for session in preview.new_sessions.iter().take(5) {
+                ui.label(format!(
+                    "{}  {}  {}  {}",
+                    clock::DateTime::local(session.start),
+                    session.work_time,
+                    session.duration.as_secs() / 60,
+                    session.task.as_deref().unwrap_or("")
+                ));
+            }
+            import = ui
+                .add_enabled(
+                    !preview.new_sessions.is_empty(),
+                    egui::Button::new("Import"),
+                )
+                .clicked();
+        }
+        if import {
+            if let Some(preview) = self.preview.take() {
+                let count = preview.new_sessions.len();
+                history.import(preview.new_sessions);
+                self.status = Some(format!("Imported {} sessions", count));
+            }
+        }
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+    }
+
+    fn read_table(&mut self) {
+        self.preview = None;
+        match parse_table(&self.text) {
+            Ok(table) => {
+                self.mapping = ColumnMapping::detect(&table.headers);
+                self.status = None;
+                self.table = Some(table);
+            }
+            Err(err) => {
+                self.table = None;
+                self.status = Some(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_durations() {
+        let minutes = DurationUnit::Minutes;
+        assert_eq!(
+            parse_duration("25", minutes),
+            Some(Duration::from_secs(1500))
+        );
+        assert_eq!(
+            parse_duration("25.5", minutes),
+            Some(Duration::from_secs(1530))
+        );
+        assert_eq!(
+            parse_duration("90", DurationUnit::Seconds),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_duration("25:30", minutes),
+            Some(Duration::from_secs(1530))
+        );
+        assert_eq!(
+            parse_duration("1:30:00", minutes),
+            Some(Duration::from_secs(5400))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for text in [
+            "inf",
+            "-inf",
+            "NaN",
+            "1e30",
+            "-5",
+            "",
+            "abc",
+            "1:2:3:4",
+            "99999999",
+            "18446744073709551615:0",
+            "0:18446744073709551615:0",
+            "18446744073709551615:0:0",
+        ] {
+            assert_eq!(
+                parse_duration(text, DurationUnit::Minutes),
+                None,
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            parse_duration("1e300", DurationUnit::Seconds),
+            None,
+            "seconds"
+        );
+    }
+
+    #[test]
+    fn rows_with_bad_durations_are_errors() {
+        let mapping = ColumnMapping {
+            start: Some(0),
+            duration: Some(1),
+            ..ColumnMapping::default()
+        };
+        assert!(record_from_row(&row(&["1700000000", "inf"]), &mapping).is_err());
+        assert!(record_from_row(&row(&["1700000000", "1e30"]), &mapping).is_err());
+        assert!(record_from_row(&row(&["99999999999999999999", "25"]), &mapping).is_err());
+        let record = record_from_row(&row(&["18446744073709551615", "25"]), &mapping).unwrap();
+        assert_eq!(record.end, record.start + 1500);
+    }
+
+    #[test]
+    fn works_out_the_missing_column() {
+        let mapping = ColumnMapping::detect(&row(&["start", "end", "minutes"]));
+        let record = record_from_row(&row(&["1700000000", "", "25"]), &mapping).unwrap();
+        assert_eq!(record.end, 1700001500);
+        let record = record_from_row(&row(&["", "1700001500", "25"]), &mapping).unwrap();
+        assert_eq!(record.start, 1700000000);
+        let record = record_from_row(&row(&["1700000000", "1700000600", ""]), &mapping).unwrap();
+        assert_eq!(record.duration, Duration::from_secs(600));
+        assert!(record_from_row(&row(&["1700000600", "1700000000", ""]), &mapping).is_err());
+        assert!(record_from_row(&row(&["1700000000", "", ""]), &mapping).is_err());
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("1700000000"), Some(1700000000));
+        assert_eq!(parse_timestamp("1700000000000"), Some(1700000000));
+        assert_eq!(parse_timestamp("2023-11-14T22:13:20Z"), Some(1700000000));
+        assert_eq!(
+            parse_timestamp("2023-11-15T00:13:20+02:00"),
+            Some(1700000000)
+        );
+        assert_eq!(
+            parse_timestamp("2023-11-14 17:13:20-0500"),
+            Some(1700000000)
+        );
+        assert_eq!(
+            parse_timestamp("2023-11-14T22:13:20.250Z"),
+            Some(1700000000)
+        );
+        for text in [
+            "",
+            "2023-13-01",
+            "2023-02-30",
+            "2023-11-14T25:00Z",
+            "yesterday",
+        ] {
+            assert_eq!(parse_timestamp(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parses_quoted_csv() {
+        let rows = parse_csv("a,b,c\r\n\"x, y\",\"say \"\"hi\"\"\",\"two\nlines\"\n1,,3");
+        assert_eq!(
+            rows,
+            vec![
+                row(&["a", "b", "c"]),
+                row(&["x, y", "say \"hi\"", "two\nlines"]),
+                row(&["1", "", "3"]),
+            ]
+        );
+        assert_eq!(
+            parse_csv("a;b\n1,5;2"),
+            vec![row(&["a", "b"]), row(&["1,5", "2"])]
+        );
+    }
+
+    #[test]
+    fn reads_json_exports() {
+        let table =
+            parse_table(r#"{"sessions": [{"start": 1, "task": "aé\"b"}, {"end": null}]}"#).unwrap();
+        assert_eq!(table.headers, row(&["start", "task", "end"]));
+        assert_eq!(
+            table.rows,
+            vec![row(&["1", "aé\"b", ""]), row(&["", "", ""])]
+        );
+        for text in ["[", "[1,", "{\"a\" 1}", "[\"open]", "[1] 2", "{\"a\": tru}"] {
+            assert!(parse_table(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn previews_new_sessions_only() {
+        let table = parse_table(
+            "start,end\n\
+             1700000030,1700001530\n\
+             1700003000,1700004500\n\
+             1700003040,1700004540\n\
+             soon,1700004540",
+        )
+        .unwrap();
+        let mut history = SessionHistory::default();
+        history.push(SessionRecord::new(WorkTimes::Work, None, 1_700_000_000));
+        let preview = preview_import(&table, &ColumnMapping::detect(&table.headers), &history);
+        let starts: Vec<u64> = preview
+            .new_sessions
+            .iter()
+            .map(|session| session.start)
+            .collect();
+        assert_eq!(starts, vec![1_700_003_000]);
+        assert_eq!(preview.duplicates, 2);
+        assert_eq!(preview.errors.len(), 1);
+        assert!(preview.errors[0].starts_with("Row 5:"));
+    }
+
+    #[test]
+    fn detects_columns() {
+        let mapping = ColumnMapping::detect(&row(&["Started At", "Task Name", "Duration (sec)"]));
+        assert_eq!(mapping.start, Some(0));
+        assert_eq!(mapping.task, Some(1));
+        assert_eq!(mapping.duration, Some(2));
+        assert_eq!(mapping.duration_unit, DurationUnit::Seconds);
+        assert_eq!(mapping.end, None);
+    }
+}
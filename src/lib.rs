@@ -1,9 +1,52 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod ambient;
 mod app;
+mod break_overlay;
+mod calendar;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+mod clock;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+mod control;
 mod custom_widgets;
+mod export;
+mod goals;
+mod idle;
+mod import;
+mod planner;
+mod report;
+mod schedule;
+mod session;
+mod sound;
+#[cfg(not(target_arch = "wasm32"))]
+mod storage;
+mod strict;
+#[cfg(not(target_arch = "wasm32"))]
+mod task_files;
+mod tasks;
+#[cfg(test)]
+mod test_util;
 mod timer;
 mod visuals;
+pub use ambient::{AmbientSound, NoiseColor};
 pub use app::TimerApp;
-pub use timer::{TimerData, TimerState, WorkTimes};
-pub use visuals::AppColorScheme;
+pub use calendar::{Calendar, Meeting};
+#[cfg(not(target_arch = "wasm32"))]
+pub use cli::run_command;
+pub use clock::{Date, DateTime};
+pub use export::{export_sessions, ExportFormat};
+pub use goals::{DailyGoal, GoalProgress, GoalUnit};
+pub use import::{parse_table, preview_import, ColumnMapping, ImportPreview};
+pub use planner::{BlockKind, PlanStart, PlannedBlock};
+pub use report::WeeklyReport;
+pub use schedule::WorkingHours;
+pub use session::{
+    IdleGap, Interruption, InterruptionKind, PauseInterval, Reflection, SessionHistory,
+    SessionRecord, StrictOverride,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use storage::{load_saved_history, APP_ID};
+pub use tasks::{Project, SessionFilter, Task, TaskList, TaskSource};
+pub use timer::{FlowtimeBreaks, PhaseWarning, TimerData, TimerEvent, TimerState, WorkTimes};
+pub use visuals::{AppColorScheme, PhaseColorScheme};
@@ -7,15 +7,26 @@ fn main() -> eframe::Result<()> {
 
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = pomodoro_timer::run_command(&args) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let native_options = eframe::NativeOptions {
-        resizable: false,
+        resizable: true,
         transparent: true,
         initial_window_size: Option::from(Vec2::new(350 as f32, 400 as f32)),
+        //Small enough for the mini mode window.
+        min_window_size: Some(Vec2::new(200.0, 80.0)),
         initial_window_pos: Option::from(Pos2::new(10 as f32, 10 as f32)),
         ..Default::default()
     };
     eframe::run_native(
-        "eframe template",
+        pomodoro_timer::APP_ID,
         native_options,
         Box::new(|cc| {
             let timer_data = pomodoro_timer::TimerData::default();
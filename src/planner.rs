@@ -0,0 +1,304 @@
+use crate::calendar::Meeting;
+use crate::clock::DateTime;
+use crate::tasks::Task;
+use crate::timer::WorkTimes;
+use egui::{Color32, Sense, Ui, Vec2};
+use std::collections::HashMap;
+use std::time::Duration;
+
+//One phase or meeting laid out on the day's timeline, times are unix seconds.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlannedBlock {
+    pub start: u64,
+    pub end: u64,
+    pub kind: BlockKind,
+    //The task of a work phase or the title of a meeting.
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlockKind {
+    Phase(WorkTimes),
+    Meeting,
+}
+
+impl PlannedBlock {
+    pub fn is_work(&self) -> bool {
+        self.kind == BlockKind::Phase(WorkTimes::Work)
+    }
+}
+
+//Where the timer is right now, the plan continues from there.
+pub struct PlanStart {
+    pub now: u64,
+    pub work_time: WorkTimes,
+    //Time left of the running phase, None if no phase is running.
+    pub remaining: Option<Duration>,
+    pub task: Option<String>,
+    pub work_since_long_break: u32,
+}
+
+//Lays out the pomodoros still estimated for the open tasks (most important first), with short
+//and long breaks in between as the timer would run them. Work phases that would run into a
+//meeting start after it instead. The plan ends with the last work phase.
+pub fn plan_day(
+    start: &PlanStart,
+    tasks: &[&Task],
+    work_times: &HashMap<WorkTimes, Duration>,
+    long_break_every: u32,
+    meetings: &[Meeting],
+) -> Vec<PlannedBlock> {
+    let length = |work_time: &WorkTimes| {
+        work_times
+            .get(work_time)
+            .copied()
+            .unwrap_or(Duration::from_secs(0))
+            .as_secs()
+    };
+    let mut queue: Vec<(String, u32)> = tasks
+        .iter()
+        .map(|task| {
+            (
+                task.name.clone(),
+                task.estimate.saturating_sub(task.pomodoros),
+            )
+        })
+        .filter(|(_, left)| *left > 0)
+        .collect();
+    let mut blocks = Vec::new();
+    let mut time = start.now;
+    let mut work_time = start.work_time;
+    let mut since_long_break = start.work_since_long_break;
+    let mut running = start.remaining;
+    let mut meeting_planned = vec![false; meetings.len()];
+    loop {
+        let task = if work_time == WorkTimes::Work {
+            if running.is_some() {
+                //The running work phase counts towards its task's estimate.
+                if let Some(entry) = queue
+                    .iter_mut()
+                    .find(|(name, _)| Some(name) == start.task.as_ref())
+                {
+                    entry.1 -= 1;
+                }
+                start.task.clone()
+            } else {
+                let (name, left) = match queue.first_mut() {
+                    Some(entry) => entry,
+                    None => break,
+                };
+                *left -= 1;
+                Some(name.clone())
+            }
+        } else if queue.iter().all(|(_, left)| *left == 0) && running.is_none() {
+            break;
+        } else {
+            None
+        };
+        queue.retain(|(_, left)| *left > 0);
+        if work_time == WorkTimes::Work && running.is_none() {
+            while let Some(index) = meetings
+                .iter()
+                .position(|meeting| meeting.start < time + length(&work_time) && meeting.end > time)
+            {
+                if !meeting_planned[index] {
+                    meeting_planned[index] = true;
+                    blocks.push(PlannedBlock {
+                        start: meetings[index].start,
+                        end: meetings[index].end,
+                        kind: BlockKind::Meeting,
+                        label: Some(meetings[index].title.clone()),
+                    });
+                }
+                time = meetings[index].end;
+            }
+        }
+        let duration = running
+            .take()
+            .map_or(length(&work_time), |left| left.as_secs());
+        blocks.push(PlannedBlock {
+            start: time,
+            end: time + duration,
+            kind: BlockKind::Phase(work_time),
+            label: task,
+        });
+        time += duration;
+        work_time = match work_time {
+            WorkTimes::Work => {
+                since_long_break += 1;
+                if long_break_every > 0 && since_long_break >= long_break_every {
+                    since_long_break = 0;
+                    WorkTimes::Long
+                } else {
+                    WorkTimes::Short
+                }
+            }
+            _ => WorkTimes::Work,
+        };
+    }
+    while blocks.last().map_or(false, |block| !block.is_work()) {
+        blocks.pop();
+    }
+    blocks
+}
+
+//When the last planned work phase ends.
+pub fn finish_time(blocks: &[PlannedBlock]) -> Option<u64> {
+    blocks.last().map(|block| block.end)
+}
+
+pub fn format_clock_time(unix: u64) -> String {
+    let time = DateTime::local(unix);
+    format!("{:02}:{:02}", time.hour, time.minute)
+}
+
+//A horizontal bar with one colored stretch per block, followed by the list of blocks.
+pub fn draw_timeline(
+    ui: &mut Ui,
+    blocks: &[PlannedBlock],
+    color: impl Fn(&PlannedBlock) -> Color32,
+) {
+    let first = blocks.iter().map(|block| block.start).min();
+    let last = blocks.iter().map(|block| block.end).max();
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            ui.label("Nothing planned, add pomodoro estimates to your tasks.");
+            return;
+        }
+    };
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 24.0), Sense::hover());
+    let span = (last - first).max(1) as f32;
+    for block in blocks {
+        let x = |time: u64| rect.left() + rect.width() * (time - first) as f32 / span;
+        let block_rect = egui::Rect::from_x_y_ranges(x(block.start)..=x(block.end), rect.y_range());
+        ui.painter()
+            .rect_filled(block_rect.shrink(1.0), 2.0, color(block));
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("planner_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for block in blocks {
+                    ui.label(format!(
+                        "{}–{}",
+                        format_clock_time(block.start),
+                        format_clock_time(block.end)
+                    ));
+                    ui.colored_label(color(block), "●");
+                    ui.label(match block.kind {
+                        BlockKind::Phase(work_time) => work_time.to_string(),
+                        BlockKind::Meeting => "Meeting".to_owned(),
+                    });
+                    ui.label(block.label.as_deref().unwrap_or(""));
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = 60;
+
+    fn work_times() -> HashMap<WorkTimes, Duration> {
+        HashMap::from([
+            (WorkTimes::Work, Duration::from_secs(25 * MINUTE)),
+            (WorkTimes::Short, Duration::from_secs(5 * MINUTE)),
+            (WorkTimes::Long, Duration::from_secs(15 * MINUTE)),
+        ])
+    }
+
+    fn task(name: &str, estimate: u32, pomodoros: u32) -> Task {
+        Task {
+            name: name.to_owned(),
+            estimate,
+            pomodoros,
+            ..Task::default()
+        }
+    }
+
+    fn idle_start() -> PlanStart {
+        PlanStart {
+            now: 1000,
+            work_time: WorkTimes::Work,
+            remaining: None,
+            task: None,
+            work_since_long_break: 0,
+        }
+    }
+
+    fn kinds(blocks: &[PlannedBlock]) -> Vec<BlockKind> {
+        blocks.iter().map(|block| block.kind).collect()
+    }
+
+    #[test]
+    fn lays_out_estimated_pomodoros() {
+        use WorkTimes::*;
+        let (a, b) = (task("A", 3, 1), task("B", 1, 0));
+        let blocks = plan_day(&idle_start(), &[&a, &b], &work_times(), 2, &[]);
+        assert_eq!(
+            kinds(&blocks),
+            [Work, Short, Work, Long, Work]
+                .map(BlockKind::Phase)
+                .to_vec()
+        );
+        let labels: Vec<Option<&str>> = blocks.iter().map(|b| b.label.as_deref()).collect();
+        assert_eq!(labels, [Some("A"), None, Some("A"), None, Some("B")]);
+        assert_eq!(
+            finish_time(&blocks),
+            Some(1000 + (3 * 25 + 5 + 15) * MINUTE)
+        );
+        assert!(blocks.windows(2).all(|pair| pair[0].end == pair[1].start));
+    }
+
+    #[test]
+    fn never_plans_long_breaks_when_off() {
+        let a = task("A", 4, 0);
+        let blocks = plan_day(&idle_start(), &[&a], &work_times(), 0, &[]);
+        assert!(!kinds(&blocks).contains(&BlockKind::Phase(WorkTimes::Long)));
+        assert_eq!(blocks.len(), 7);
+    }
+
+    #[test]
+    fn continues_the_running_phase() {
+        let a = task("A", 2, 0);
+        let start = PlanStart {
+            remaining: Some(Duration::from_secs(10 * MINUTE)),
+            task: Some(String::from("A")),
+            ..idle_start()
+        };
+        let blocks = plan_day(&start, &[&a], &work_times(), 0, &[]);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].end, 1000 + 10 * MINUTE);
+        assert_eq!(blocks[2].label.as_deref(), Some("A"));
+        assert_eq!(
+            plan_day(&idle_start(), &[], &work_times(), 0, &[]),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn moves_work_after_meetings() {
+        let a = task("A", 2, 0);
+        let meeting = Meeting {
+            start: 1000 + 40 * MINUTE,
+            end: 1000 + 70 * MINUTE,
+            title: String::from("Sync"),
+        };
+        let blocks = plan_day(&idle_start(), &[&a], &work_times(), 0, &[meeting.clone()]);
+        assert_eq!(
+            kinds(&blocks),
+            vec![
+                BlockKind::Phase(WorkTimes::Work),
+                BlockKind::Phase(WorkTimes::Short),
+                BlockKind::Meeting,
+                BlockKind::Phase(WorkTimes::Work),
+            ]
+        );
+        assert_eq!(blocks[2].label.as_deref(), Some("Sync"));
+        assert_eq!(blocks[3].start, meeting.end);
+    }
+}
@@ -0,0 +1,494 @@
+use crate::clock::{self, Date, DateTime, DAY_NAMES};
+use crate::session::{InterruptionKind, Reflection, SessionHistory};
+use crate::tasks::SessionFilter;
+use crate::timer::WorkTimes;
+use std::time::Duration;
+
+//Size of the bar charts in the html report.
+const CHART_WIDTH: f32 = 600.0;
+const CHART_HEIGHT: f32 = 160.0;
+
+//Numbers for one week of work sessions, monday to sunday. Sessions count towards the local date
+//and hour they started at.
+pub struct WeeklyReport {
+    pub week_start: Date,
+    pub focus_per_day: [Duration; 7],
+    //Most focus first, sessions without a task are listed as "(no task)".
+    pub focus_per_task: Vec<(String, Duration)>,
+    //Same for projects, "(no project)" for sessions without one.
+    pub focus_per_project: Vec<(String, Duration)>,
+    pub focus_per_hour: [Duration; 24],
+    pub work_sessions: usize,
+    pub completed_sessions: usize,
+    pub internal_interruptions: usize,
+    pub external_interruptions: usize,
+    //Session start, task and answers, oldest first.
+    pub reflections: Vec<(u64, Option<String>, Reflection)>,
+    //How often each break activity was done, most often first.
+    pub break_activities: Vec<(String, u32)>,
+}
+
+//Monday of the week the date lies in.
+pub fn week_start(date: Date) -> Date {
+    date.add_days(-i64::from(date.weekday()))
+}
+
+impl WeeklyReport {
+    pub fn new(history: &SessionHistory, week_start: Date, filter: &SessionFilter) -> WeeklyReport {
+        let mut report = WeeklyReport {
+            week_start,
+            focus_per_day: [Duration::from_secs(0); 7],
+            focus_per_task: Vec::new(),
+            focus_per_project: Vec::new(),
+            focus_per_hour: [Duration::from_secs(0); 24],
+            work_sessions: 0,
+            completed_sessions: 0,
+            internal_interruptions: 0,
+            external_interruptions: 0,
+            reflections: Vec::new(),
+            break_activities: Vec::new(),
+        };
+        let mut sessions = history.in_date_range(Some(week_start), Some(week_start.add_days(6)));
+        sessions.sort_by_key(|session| session.start);
+        for session in sessions
+            .iter()
+            .filter(|session| session.work_time == WorkTimes::Work && filter.matches(session))
+        {
+            let start = DateTime::local(session.start);
+            let day = (start.date.to_days() - week_start.to_days()) as usize;
+            report.focus_per_day[day] += session.duration;
+            report.focus_per_hour[start.hour as usize] += session.duration;
+            add_focus(
+                &mut report.focus_per_task,
+                session.task.as_deref().unwrap_or("(no task)"),
+                session.duration,
+            );
+            add_focus(
+                &mut report.focus_per_project,
+                session.project.as_deref().unwrap_or("(no project)"),
+                session.duration,
+            );
+            report.work_sessions += 1;
+            if session.completed {
+                report.completed_sessions += 1;
+            }
+            for interruption in &session.interruptions {
+                match interruption.kind {
+                    InterruptionKind::Internal => report.internal_interruptions += 1,
+                    InterruptionKind::External => report.external_interruptions += 1,
+                }
+            }
+            if let Some(reflection) = &session.reflection {
+                report
+                    .reflections
+                    .push((session.start, session.task.clone(), reflection.clone()));
+            }
+        }
+        //Breaks have no project or tags of their own, so the filter does not apply to them.
+        for activity in sessions
+            .iter()
+            .filter_map(|session| session.activity.as_ref())
+        {
+            match report
+                .break_activities
+                .iter_mut()
+                .find(|(name, _)| name == activity)
+            {
+                Some((_, count)) => *count += 1,
+                None => report.break_activities.push((activity.clone(), 1)),
+            }
+        }
+        report
+            .break_activities
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for totals in [&mut report.focus_per_task, &mut report.focus_per_project] {
+            totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        }
+        report
+    }
+
+    pub fn total_focus(&self) -> Duration {
+        self.focus_per_day.iter().sum()
+    }
+
+    //Percentage of work sessions that ran to the end, None without any sessions.
+    pub fn completion_rate(&self) -> Option<u32> {
+        if self.work_sessions == 0 {
+            None
+        } else {
+            Some((self.completed_sessions * 100 / self.work_sessions) as u32)
+        }
+    }
+
+    //The (up to) three hours of the day with the most focus.
+    pub fn best_hours(&self) -> Vec<(usize, Duration)> {
+        let mut hours: Vec<(usize, Duration)> = self
+            .focus_per_hour
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, focus)| !focus.is_zero())
+            .collect();
+        hours.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hours.truncate(3);
+        hours
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Weekly report {} – {}",
+            self.week_start,
+            self.week_start.add_days(6)
+        )
+    }
+
+    fn completion_text(&self) -> String {
+        match self.completion_rate() {
+            Some(rate) => format!(
+                "{}/{} sessions completed ({}%)",
+                self.completed_sessions, self.work_sessions, rate
+            ),
+            None => String::from("No work sessions"),
+        }
+    }
+
+    fn interruptions_text(&self) -> String {
+        format!(
+            "{} internal, {} external interruptions",
+            self.internal_interruptions, self.external_interruptions
+        )
+    }
+
+    //"Stretch ×3, Go for a walk ×1"
+    pub fn activities_text(&self) -> String {
+        self.break_activities
+            .iter()
+            .map(|(name, count)| format!("{} ×{}", name, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    fn reflection_text(start: u64, task: &Option<String>, reflection: &Reflection) -> String {
+        let start = DateTime::local(start);
+        let mut text = format!(
+            "{} {:02}:{:02}",
+            DAY_NAMES[start.date.weekday() as usize],
+            start.hour,
+            start.minute
+        );
+        if let Some(task) = task {
+            text.push_str(&format!(", {}", task));
+        }
+        format!(
+            "{}: {} (focus {}/5, energy {}/5)",
+            text, reflection.accomplished, reflection.focus, reflection.energy
+        )
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n\n", self.title());
+        md.push_str("## Focus per day\n\n| Day | Focus |\n| --- | --- |\n");
+        for (day, focus) in self.focus_per_day.iter().enumerate() {
+            md.push_str(&format!(
+                "| {} {} | {} |\n",
+                DAY_NAMES[day],
+                self.week_start.add_days(day as i64),
+                format_focus(*focus)
+            ));
+        }
+        md.push_str(&format!(
+            "\n**Total:** {}\n\n",
+            format_focus(self.total_focus())
+        ));
+        for (heading, totals) in [
+            ("Project", &self.focus_per_project),
+            ("Task", &self.focus_per_task),
+        ] {
+            md.push_str(&format!("## Focus per {}\n\n", heading.to_lowercase()));
+            if totals.is_empty() {
+                md.push_str("No focus time this week.\n\n");
+                continue;
+            }
+            md.push_str(&format!("| {} | Focus |\n| --- | --- |\n", heading));
+            for (name, focus) in totals {
+                md.push_str(&format!(
+                    "| {} | {} |\n",
+                    name.replace('|', "\\|"),
+                    format_focus(*focus)
+                ));
+            }
+            md.push('\n');
+        }
+        md.push_str(&format!(
+            "## Sessions\n\n- {}\n- {}\n\n",
+            self.completion_text(),
+            self.interruptions_text()
+        ));
+        md.push_str("## Best focus hours\n\n");
+        for (hour, focus) in self.best_hours() {
+            md.push_str(&format!(
+                "- {:02}:00–{:02}:00: {}\n",
+                hour,
+                hour + 1,
+                format_focus(focus)
+            ));
+        }
+        if self.best_hours().is_empty() {
+            md.push_str("No focus time this week.\n");
+        }
+        if !self.break_activities.is_empty() {
+            md.push_str(&format!(
+                "\n## Break activities\n\n{}\n",
+                self.activities_text()
+            ));
+        }
+        if !self.reflections.is_empty() {
+            md.push_str("\n## Reflections\n\n");
+            for (start, task, reflection) in &self.reflections {
+                md.push_str(&format!(
+                    "- {}\n",
+                    WeeklyReport::reflection_text(*start, task, reflection)
+                ));
+            }
+        }
+        md
+    }
+
+    //A single html file with inline styles and svg charts, so it can be opened or shared as is.
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>body {{ font-family: sans-serif; max-width: 640px; margin: 2em auto; }} \
+             table {{ border-collapse: collapse; }} td, th {{ padding: 2px 12px; text-align: left; }}\
+             </style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            html_escape(&self.title()),
+            html_escape(&self.title())
+        );
+        let day_labels: Vec<String> = DAY_NAMES.iter().map(|day| day.to_string()).collect();
+        html.push_str("<h2>Focus per day</h2>\n");
+        html.push_str(&bar_chart(&self.focus_per_day, &day_labels));
+        html.push_str(&format!(
+            "<p><b>Total:</b> {}</p>\n",
+            format_focus(self.total_focus())
+        ));
+        for (heading, totals) in [
+            ("Project", &self.focus_per_project),
+            ("Task", &self.focus_per_task),
+        ] {
+            html.push_str(&format!("<h2>Focus per {}</h2>\n", heading.to_lowercase()));
+            if totals.is_empty() {
+                html.push_str("<p>No focus time this week.</p>\n");
+                continue;
+            }
+            html.push_str(&format!(
+                "<table>\n<tr><th>{}</th><th>Focus</th></tr>\n",
+                heading
+            ));
+            for (name, focus) in totals {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td></tr>\n",
+                    html_escape(name),
+                    format_focus(*focus)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str(&format!(
+            "<h2>Sessions</h2>\n<ul>\n<li>{}</li>\n<li>{}</li>\n</ul>\n",
+            self.completion_text(),
+            self.interruptions_text()
+        ));
+        let hour_labels: Vec<String> = (0..24)
+            .map(|hour| {
+                if hour % 3 == 0 {
+                    format!("{:02}", hour)
+                } else {
+                    String::new()
+                }
+            })
+            .collect();
+        html.push_str("<h2>Focus per hour</h2>\n");
+        html.push_str(&bar_chart(&self.focus_per_hour, &hour_labels));
+        if !self.break_activities.is_empty() {
+            html.push_str(&format!(
+                "<h2>Break activities</h2>\n<p>{}</p>\n",
+                html_escape(&self.activities_text())
+            ));
+        }
+        if !self.reflections.is_empty() {
+            html.push_str("<h2>Reflections</h2>\n<ul>\n");
+            for (start, task, reflection) in &self.reflections {
+                html.push_str(&format!(
+                    "<li>{}</li>\n",
+                    html_escape(&WeeklyReport::reflection_text(*start, task, reflection))
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn add_focus(totals: &mut Vec<(String, Duration)>, name: &str, focus: Duration) {
+    match totals.iter_mut().find(|(total_name, _)| total_name == name) {
+        Some((_, total)) => *total += focus,
+        None => totals.push((name.to_owned(), focus)),
+    }
+}
+
+//"1 h 40 min" or "25 min".
+pub fn format_focus(focus: Duration) -> String {
+    let minutes = focus.as_secs() / 60;
+    if minutes >= 60 {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    } else {
+        format!("{} min", minutes)
+    }
+}
+
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+//Vertical bars in minutes with a label under each bar.
+fn bar_chart(values: &[Duration], labels: &[String]) -> String {
+    let label_height = 16.0;
+    let bar_area = CHART_HEIGHT - label_height;
+    let slot = CHART_WIDTH / values.len() as f32;
+    let max_minutes = values
+        .iter()
+        .map(|value| value.as_secs() / 60)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-size=\"11\">\n",
+        CHART_WIDTH, CHART_HEIGHT
+    );
+    for (index, (value, label)) in values.iter().zip(labels).enumerate() {
+        let minutes = value.as_secs() / 60;
+        let height = bar_area * minutes as f32 / max_minutes;
+        let x = index as f32 * slot;
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#d95d39\">\
+             <title>{} min</title></rect>\n",
+            x + slot * 0.15,
+            bar_area - height,
+            slot * 0.7,
+            height,
+            minutes
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+            x + slot / 2.0,
+            CHART_HEIGHT - 4.0,
+            html_escape(label)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+//The report for the week that contains today.
+pub fn this_week() -> Date {
+    week_start(DateTime::local(clock::now_unix()).date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{Interruption, SessionRecord};
+    use crate::test_util::{work_session, DAY as WEEK};
+
+    fn session(day: i64, hour: u32, minutes: u64, task: Option<&str>) -> SessionRecord {
+        SessionRecord {
+            task: task.map(str::to_owned),
+            ..work_session(day, hour, minutes)
+        }
+    }
+
+    fn history() -> SessionHistory {
+        let mut interrupted = session(2, 10, 10, Some("B"));
+        interrupted.completed = false;
+        interrupted.project = Some(String::from("P"));
+        interrupted.interruptions.push(Interruption {
+            time: interrupted.start,
+            kind: InterruptionKind::External,
+            note: None,
+        });
+        let mut stretch = session(0, 9, 5, None);
+        stretch.work_time = WorkTimes::Short;
+        stretch.activity = Some(String::from("Stretch"));
+        let mut history = SessionHistory::default();
+        history.import(vec![
+            session(-1, 10, 25, Some("before")),
+            session(0, 9, 25, Some("A")),
+            session(0, 10, 25, Some("A")),
+            interrupted,
+            session(6, 14, 25, None),
+            session(7, 10, 25, Some("after")),
+            stretch,
+        ]);
+        history
+    }
+
+    #[test]
+    fn sums_up_one_week() {
+        let report = WeeklyReport::new(&history(), WEEK, &SessionFilter::default());
+        assert_eq!(report.focus_per_day[0], Duration::from_secs(50 * 60));
+        assert_eq!(report.focus_per_day[2], Duration::from_secs(10 * 60));
+        assert_eq!(report.focus_per_day[6], Duration::from_secs(25 * 60));
+        assert_eq!(report.total_focus(), Duration::from_secs(85 * 60));
+        assert_eq!(
+            report.focus_per_task,
+            vec![
+                (String::from("A"), Duration::from_secs(50 * 60)),
+                (String::from("(no task)"), Duration::from_secs(25 * 60)),
+                (String::from("B"), Duration::from_secs(10 * 60)),
+            ]
+        );
+        assert_eq!(report.work_sessions, 4);
+        assert_eq!(report.completion_rate(), Some(75));
+        assert_eq!(report.external_interruptions, 1);
+        assert_eq!(report.activities_text(), "Stretch ×1");
+        assert_eq!(
+            report.best_hours(),
+            vec![
+                (10, Duration::from_secs(35 * 60)),
+                (9, Duration::from_secs(25 * 60)),
+                (14, Duration::from_secs(25 * 60)),
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_by_project() {
+        let filter = SessionFilter {
+            project: Some(String::from("P")),
+            tag: None,
+        };
+        let report = WeeklyReport::new(&history(), WEEK, &filter);
+        assert_eq!(report.work_sessions, 1);
+        assert_eq!(report.completion_rate(), Some(0));
+        let empty = WeeklyReport::new(&SessionHistory::default(), WEEK, &filter);
+        assert_eq!(empty.completion_rate(), None);
+        assert!(empty.to_markdown().contains("No work sessions"));
+    }
+
+    #[test]
+    fn formats_weeks_and_text() {
+        assert_eq!(week_start(WEEK.add_days(6)), WEEK);
+        assert_eq!(week_start(WEEK.add_days(7)), WEEK.add_days(7));
+        assert_eq!(week_start(WEEK.add_days(-1)), WEEK.add_days(-7));
+        assert_eq!(format_focus(Duration::from_secs(25 * 60 + 59)), "25 min");
+        assert_eq!(format_focus(Duration::from_secs(100 * 60)), "1 h 40 min");
+        assert_eq!(
+            html_escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::clock::{self, DateTime};
+
+//Working hours per weekday, used to start the first work session of a block automatically or to
+//remind that one has begun.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WorkingHours {
+    pub enabled: bool,
+    //(start, end) seconds of the local day, indexed by weekday with 0 being monday.
+    pub days: [Vec<(u32, u32)>; 7],
+    //Start a work phase when a block begins and no timer is running.
+    pub auto_start: bool,
+    //Play the alert and show a reminder when a block begins and no timer is running.
+    pub remind: bool,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        let office = vec![(9 * 3600, 12 * 3600), (13 * 3600, 17 * 3600)];
+        WorkingHours {
+            enabled: false,
+            days: [
+                office.clone(),
+                office.clone(),
+                office.clone(),
+                office.clone(),
+                office,
+                Vec::new(),
+                Vec::new(),
+            ],
+            auto_start: false,
+            remind: true,
+        }
+    }
+}
+
+impl WorkingHours {
+    pub fn is_working_time(&self, unix: u64) -> bool {
+        let time = DateTime::local(unix);
+        let second = time.seconds_of_day();
+        self.enabled
+            && self.days[time.date.weekday() as usize]
+                .iter()
+                .any(|(start, end)| (*start..*end).contains(&second))
+    }
+
+    //The latest start of a block after `from` and up to `to`, both unix seconds.
+    pub fn block_started(&self, from: u64, to: u64) -> Option<u64> {
+        if !self.enabled || to <= from {
+            return None;
+        }
+        let first_day = DateTime::local(from).date;
+        let last_day = DateTime::local(to).date;
+        let mut started = None;
+        let mut day = first_day;
+        while day <= last_day {
+            for (start, _) in &self.days[day.weekday() as usize] {
+                let start = clock::local_to_unix(day, *start);
+                if from < start && start <= to {
+                    started = started.max(Some(start));
+                }
+            }
+            day = day.add_days(1);
+        }
+        started
+    }
+
+    //"9:00-12:00, 13:00-17:00"
+    pub fn day_text(&self, weekday: usize) -> String {
+        self.days[weekday]
+            .iter()
+            .map(|(start, end)| format!("{}-{}", format_time(*start), format_time(*end)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    //Parses what day_text writes, None if a block is not valid. An empty text is a day off.
+    pub fn parse_day(text: &str) -> Option<Vec<(u32, u32)>> {
+        let mut blocks = text
+            .split(',')
+            .filter(|block| !block.trim().is_empty())
+            .map(|block| {
+                let (start, end) = block.split_once('-')?;
+                let (start, end) = (parse_time(start)?, parse_time(end)?);
+                Some((start, end)).filter(|_| start < end)
+            })
+            .collect::<Option<Vec<(u32, u32)>>>()?;
+        blocks.sort();
+        Some(blocks)
+    }
+}
+
+fn format_time(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 3600, seconds / 60 % 60)
+}
+
+//"9:30" or "9" into seconds of the day, "24:00" is allowed as the end of the day.
+fn parse_time(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let (hour, minute) = text.split_once(':').unwrap_or((text, "0"));
+    let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    if hour > 24 || minute >= 60 || (hour == 24 && minute > 0) {
+        return None;
+    }
+    Some(hour * 3600 + minute * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Date;
+
+    //2026-10-19 is a monday.
+    fn monday_at(hour: u32, minute: u32) -> u64 {
+        clock::local_to_unix(
+            Date::parse("2026-10-19").unwrap(),
+            hour * 3600 + minute * 60,
+        )
+    }
+
+    fn enabled() -> WorkingHours {
+        WorkingHours {
+            enabled: true,
+            ..WorkingHours::default()
+        }
+    }
+
+    #[test]
+    fn finds_block_starts() {
+        let hours = enabled();
+        assert_eq!(
+            hours.block_started(monday_at(8, 59), monday_at(9, 1)),
+            Some(monday_at(9, 0))
+        );
+        assert_eq!(
+            hours.block_started(monday_at(8, 0), monday_at(14, 0)),
+            Some(monday_at(13, 0))
+        );
+        assert_eq!(
+            hours.block_started(monday_at(9, 0), monday_at(12, 59)),
+            None
+        );
+        assert_eq!(hours.block_started(monday_at(10, 0), monday_at(9, 0)), None);
+        //Saturday and sunday are off, monday 9:00 is the next block.
+        let friday_evening = monday_at(18, 0) - 3 * 86400;
+        assert_eq!(
+            hours.block_started(friday_evening, monday_at(9, 30)),
+            Some(monday_at(9, 0))
+        );
+        assert_eq!(
+            WorkingHours::default().block_started(monday_at(8, 0), monday_at(10, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn knows_working_time() {
+        let hours = enabled();
+        assert!(hours.is_working_time(monday_at(9, 0)));
+        assert!(!hours.is_working_time(monday_at(12, 0)));
+        assert!(!hours.is_working_time(monday_at(10, 0) + 5 * 86400));
+        assert!(!WorkingHours::default().is_working_time(monday_at(10, 0)));
+    }
+
+    #[test]
+    fn parses_day_texts() {
+        let hours = WorkingHours::default();
+        assert_eq!(
+            WorkingHours::parse_day(&hours.day_text(0)),
+            Some(hours.days[0].clone())
+        );
+        assert_eq!(
+            WorkingHours::parse_day("13:30-24:00, 8-12"),
+            Some(vec![(8 * 3600, 12 * 3600), (13 * 3600 + 1800, 24 * 3600)])
+        );
+        assert_eq!(WorkingHours::parse_day(" "), Some(Vec::new()));
+        for text in [
+            "9", "12-9", "9:60-10", "25-26", "24:30-25", "a-b", "9-10-11",
+        ] {
+            assert_eq!(WorkingHours::parse_day(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use crate::clock::{Date, DateTime};
+use crate::timer::WorkTimes;
+use std::time::Duration;
+
+//One phase of the timer from the moment it was started until it finished or was stopped.
+//Timestamps are seconds since the unix epoch.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct SessionRecord {
+    pub work_time: WorkTimes,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub start: u64,
+    pub end: u64,
+    //Time that was counted down on the timer, pauses are not included.
+    pub duration: Duration,
+    pub completed: bool,
+    pub idle_gaps: Vec<IdleGap>,
+    //Time added to the phase while it was running, in the order it was added.
+    #[serde(default)]
+    pub extensions: Vec<Duration>,
+    //How long the timer counted up past the end of the phase before it was acknowledged.
+    #[serde(default)]
+    pub overtime: Duration,
+    #[serde(default)]
+    pub interruptions: Vec<Interruption>,
+    #[serde(default)]
+    pub pauses: Vec<PauseInterval>,
+    #[serde(default)]
+    pub reflection: Option<Reflection>,
+    //Break activity the user did during a break.
+    #[serde(default)]
+    pub activity: Option<String>,
+    //Times the user overrode strict mode to pause, skip or switch away from the work phase.
+    #[serde(default)]
+    pub overrides: Vec<StrictOverride>,
+}
+
+//A stretch of time where the user was away from the computer during a session. If the idle time
+//was not kept it has been taken off the session's duration.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct IdleGap {
+    pub start: u64,
+    pub duration: Duration,
+    pub kept: bool,
+}
+
+//Something that broke the focus during a work session, either from the user's own mind
+//(internal) or from someone or something else (external).
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Interruption {
+    pub time: u64,
+    pub kind: InterruptionKind,
+    pub note: Option<String>,
+}
+
+//Strict mode was overridden to do `action` ("Pause", "Skip", ...) to the work session.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct StrictOverride {
+    pub time: u64,
+    pub action: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum InterruptionKind {
+    Internal,
+    External,
+}
+
+//Answers to the prompt shown after a completed work session, ratings go from 1 to 5.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Reflection {
+    pub accomplished: String,
+    pub focus: u8,
+    pub energy: u8,
+}
+
+impl Default for Reflection {
+    fn default() -> Self {
+        Reflection {
+            accomplished: String::new(),
+            focus: 3,
+            energy: 3,
+        }
+    }
+}
+
+//A pause of the timer, `end` equals `start` while the pause is still going on.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PauseInterval {
+    pub start: u64,
+    pub end: u64,
+    pub reason: Option<String>,
+}
+
+impl PauseInterval {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.end.saturating_sub(self.start))
+    }
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SessionHistory {
+    sessions: Vec<SessionRecord>,
+}
+
+impl SessionRecord {
+    pub fn new(work_time: WorkTimes, task: Option<String>, start: u64) -> SessionRecord {
+        SessionRecord {
+            work_time,
+            task,
+            project: None,
+            tags: Vec::new(),
+            start,
+            end: start,
+            duration: Duration::from_secs(0),
+            completed: false,
+            idle_gaps: Vec::new(),
+            extensions: Vec::new(),
+            overtime: Duration::from_secs(0),
+            interruptions: Vec::new(),
+            pauses: Vec::new(),
+            reflection: None,
+            activity: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    //Case insensitive search through the task, the reflection and the interruption notes.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        let contains = |text: &str| text.to_lowercase().contains(&query);
+        query.is_empty()
+            || self.task.as_deref().map_or(false, contains)
+            || self
+                .reflection
+                .as_ref()
+                .map_or(false, |reflection| contains(&reflection.accomplished))
+            || self
+                .interruptions
+                .iter()
+                .filter_map(|interruption| interruption.note.as_deref())
+                .any(contains)
+    }
+
+    //Given up on: ended early after strict mode was overridden.
+    pub fn is_abandoned(&self) -> bool {
+        !self.completed && !self.overrides.is_empty()
+    }
+
+    //Tally marks as used on the pomodoro sheet, ' for internal and - for external interruptions.
+    pub fn interruption_tally(&self) -> String {
+        self.interruptions
+            .iter()
+            .map(|interruption| match interruption.kind {
+                InterruptionKind::Internal => '\'',
+                InterruptionKind::External => '-',
+            })
+            .collect()
+    }
+
+    //Two records of the same phase starting within a minute of each other are taken to be the
+    //same session, e.g. when importing an export of this app or importing a file twice.
+    pub fn is_duplicate_of(&self, other: &SessionRecord) -> bool {
+        self.work_time == other.work_time && self.start.abs_diff(other.start) <= 60
+    }
+}
+
+impl SessionHistory {
+    pub fn sessions(&self) -> &Vec<SessionRecord> {
+        &self.sessions
+    }
+    pub fn set_reflection(&mut self, start: u64, reflection: Reflection) {
+        if let Some(session) = self
+            .sessions
+            .iter_mut()
+            .rev()
+            .find(|session| session.start == start && session.work_time == WorkTimes::Work)
+        {
+            session.reflection = Some(reflection);
+        }
+    }
+    //Assigns a project to a session after the fact, sessions are identified by phase and start.
+    pub fn set_project(&mut self, work_time: WorkTimes, start: u64, project: Option<String>) {
+        if let Some(session) = self
+            .sessions
+            .iter_mut()
+            .find(|session| session.start == start && session.work_time == work_time)
+        {
+            session.project = project;
+        }
+    }
+    pub fn push(&mut self, session: SessionRecord) {
+        self.sessions.push(session);
+    }
+    //Adds sessions from elsewhere, keeping the history in the order the sessions started.
+    pub fn import(&mut self, sessions: Vec<SessionRecord>) {
+        self.sessions.extend(sessions);
+        self.sessions.sort_by_key(|session| session.start);
+    }
+    //Number of internal and external interruptions over all sessions.
+    pub fn interruption_counts(&self) -> (usize, usize) {
+        self.sessions
+            .iter()
+            .flat_map(|session| &session.interruptions)
+            .fold(
+                (0, 0),
+                |(internal, external), interruption| match interruption.kind {
+                    InterruptionKind::Internal => (internal + 1, external),
+                    InterruptionKind::External => (internal, external + 1),
+                },
+            )
+    }
+
+    //Total paused time per local day of the pause start, oldest day first.
+    pub fn paused_per_day(&self) -> Vec<(Date, Duration)> {
+        let mut per_day: Vec<(Date, Duration)> = Vec::new();
+        for pause in self.sessions.iter().flat_map(|session| &session.pauses) {
+            let date = DateTime::local(pause.start).date;
+            match per_day.iter_mut().find(|(day, _)| *day == date) {
+                Some((_, total)) => *total += pause.duration(),
+                None => per_day.push((date, pause.duration())),
+            }
+        }
+        per_day.sort();
+        per_day
+    }
+
+    //How often each pause reason was given, most common first.
+    pub fn pause_reason_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for reason in self
+            .sessions
+            .iter()
+            .flat_map(|session| &session.pauses)
+            .filter_map(|pause| pause.reason.as_ref())
+        {
+            match counts.iter_mut().find(|(name, _)| name == reason) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((reason.clone(), 1)),
+            }
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    //Sessions that started on a local date between `from` and `to`, both ends included and
+    //unbounded when None.
+    pub fn in_date_range(&self, from: Option<Date>, to: Option<Date>) -> Vec<&SessionRecord> {
+        self.sessions
+            .iter()
+            .filter(|session| {
+                let date = DateTime::local(session.start).date;
+                from.map_or(true, |from| date >= from) && to.map_or(true, |to| date <= to)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{work_session, DAY};
+
+    fn pause(start: u64, seconds: u64, reason: Option<&str>) -> PauseInterval {
+        PauseInterval {
+            start,
+            end: start + seconds,
+            reason: reason.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn searches_tasks_reflections_and_notes() {
+        let mut session = SessionRecord {
+            task: Some(String::from("Write Docs")),
+            ..work_session(0, 9, 25)
+        };
+        session.interruptions.push(Interruption {
+            time: session.start,
+            kind: InterruptionKind::External,
+            note: Some(String::from("Phone call")),
+        });
+        let mut history = SessionHistory::default();
+        let mut same_start = session.clone();
+        same_start.work_time = WorkTimes::Short;
+        history.import(vec![session, same_start]);
+        let start = history.sessions()[0].start;
+        history.set_reflection(
+            start,
+            Reflection {
+                accomplished: String::from("Finished the intro"),
+                ..Reflection::default()
+            },
+        );
+        let session = &history.sessions()[0];
+        for query in ["", "docs", " PHONE ", "intro"] {
+            assert!(session.matches(query), "{}", query);
+        }
+        assert!(!session.matches("email"));
+        assert!(!history.sessions()[1].matches("intro"));
+    }
+
+    #[test]
+    fn tallies_interruptions() {
+        let interruption = |kind| Interruption {
+            time: 0,
+            kind,
+            note: None,
+        };
+        let mut first = work_session(0, 9, 25);
+        first.interruptions = vec![
+            interruption(InterruptionKind::External),
+            interruption(InterruptionKind::Internal),
+            interruption(InterruptionKind::Internal),
+        ];
+        assert_eq!(first.interruption_tally(), "-''");
+        let mut second = work_session(0, 10, 25);
+        second.interruptions = vec![interruption(InterruptionKind::External)];
+        let mut history = SessionHistory::default();
+        history.import(vec![first, second, work_session(0, 11, 25)]);
+        assert_eq!(history.interruption_counts(), (2, 2));
+    }
+
+    #[test]
+    fn finds_duplicates() {
+        let session = SessionRecord::new(WorkTimes::Work, None, 1000);
+        assert!(session.is_duplicate_of(&SessionRecord::new(WorkTimes::Work, None, 1060)));
+        assert!(!session.is_duplicate_of(&SessionRecord::new(WorkTimes::Work, None, 1061)));
+        assert!(!session.is_duplicate_of(&SessionRecord::new(WorkTimes::Short, None, 1000)));
+    }
+
+    #[test]
+    fn selects_sessions_by_local_day() {
+        let mut history = SessionHistory::default();
+        history.import(vec![work_session(1, 0, 25), work_session(0, 23, 25)]);
+        assert_eq!(history.in_date_range(Some(DAY), Some(DAY)).len(), 1);
+        assert_eq!(history.in_date_range(Some(DAY.add_days(1)), None).len(), 1);
+        assert_eq!(history.in_date_range(None, Some(DAY.add_days(-1))).len(), 0);
+        assert_eq!(history.in_date_range(None, None).len(), 2);
+    }
+
+    #[test]
+    fn sums_pauses_by_local_day() {
+        let mut first = work_session(0, 23, 25);
+        first.pauses.push(pause(first.start, 120, Some("Call")));
+        first
+            .pauses
+            .push(pause(first.start + 600, 60, Some("Coffee")));
+        let mut second = work_session(1, 0, 25);
+        second.pauses.push(pause(second.start, 30, Some("Call")));
+        second.pauses.push(pause(second.start + 60, 0, None));
+        let mut history = SessionHistory::default();
+        history.import(vec![second, first]);
+        assert_eq!(
+            history.paused_per_day(),
+            vec![
+                (DAY, Duration::from_secs(180)),
+                (DAY.add_days(1), Duration::from_secs(30)),
+            ]
+        );
+        assert_eq!(
+            history.pause_reason_counts(),
+            vec![(String::from("Call"), 2), (String::from("Coffee"), 1)]
+        );
+    }
+}
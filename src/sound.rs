@@ -0,0 +1,167 @@
+//Plays the alert sounds and the background sounds during work. Everything goes through a single
+//player thread, which opens the audio output when there is something to play and closes it again
+//once all sounds have ended or faded out. In between it waits for the next sound.
+use crate::ambient::{AmbientSound, Mix, Noise, NoiseColor, Tick};
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+//Volume change per fade step, a full fade takes 2 seconds.
+const FADE_STEP: Duration = Duration::from_millis(50);
+const FADE_PER_STEP: f32 = 0.025;
+//How often the player looks whether the alerts have finished playing.
+const ALERT_CHECK: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct AlertPlayingError;
+
+enum Message {
+    //Volumes the background sounds should move towards.
+    Ambient(Mix),
+    //A sound that is played once, e.g. an alert.
+    Play(Box<dyn Source<Item = f32> + Send>),
+}
+
+//Hands sounds to the player thread, which is only started once something is to be heard.
+#[derive(Default)]
+pub struct SoundPlayer {
+    sender: Option<Sender<Message>>,
+    ambient: Mix,
+}
+
+impl SoundPlayer {
+    pub fn play_alert(&mut self, audio_path: &str) -> Result<(), AlertPlayingError> {
+        self.play_sound(audio_path, 1.0)
+    }
+
+    //Plays a sound file once at a volume from 0.0 to 1.0.
+    pub fn play_sound(&mut self, audio_path: &str, volume: f32) -> Result<(), AlertPlayingError> {
+        let file = File::open(audio_path).map_err(|_| AlertPlayingError)?;
+        let source = rodio::Decoder::new(BufReader::new(file)).map_err(|_| AlertPlayingError)?;
+        self.send(Message::Play(Box::new(
+            source.amplify(volume).convert_samples(),
+        )));
+        Ok(())
+    }
+
+    //Called every frame, only changes are passed on.
+    pub fn update_ambient(&mut self, settings: &AmbientSound, working: bool) {
+        let mix = settings.mix(working);
+        if mix == self.ambient {
+            return;
+        }
+        self.ambient = mix;
+        self.send(Message::Ambient(mix));
+    }
+
+    fn send(&mut self, message: Message) {
+        //There are no threads to play on in the browser.
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        let sender = self.sender.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || play(receiver));
+            sender
+        });
+        if sender.send(message).is_err() {
+            //The thread has ended, the next sound starts a new one.
+            self.sender = None;
+        }
+    }
+}
+
+//Runs on the player thread until the app goes away. The output stream has to stay on the thread
+//that opened it.
+fn play(receiver: Receiver<Message>) {
+    let mut output: Option<(OutputStream, OutputStreamHandle)> = None;
+    let mut tick: Option<Sink> = None;
+    let mut noise: Option<(NoiseColor, Sink)> = None;
+    let mut sounds: Vec<Sink> = Vec::new();
+    let mut target = Mix::default();
+    loop {
+        let fading = tick
+            .as_ref()
+            .map_or(false, |sink| sink.volume() != target.tick)
+            || noise
+                .as_ref()
+                .map_or(false, |(_, sink)| sink.volume() != target.noise);
+        let received = if fading {
+            receiver.recv_timeout(FADE_STEP)
+        } else if !sounds.is_empty() {
+            receiver.recv_timeout(ALERT_CHECK)
+        } else {
+            receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+        match received {
+            Ok(Message::Ambient(mix)) => target = mix,
+            Ok(Message::Play(source)) => {
+                if let Some(sink) = new_sink(&mut output) {
+                    sink.append(source);
+                    sounds.push(sink);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if target.tick > 0.0 && tick.is_none() {
+            tick = new_sink(&mut output).map(|sink| {
+                sink.set_volume(0.0);
+                sink.append(Tick::default());
+                sink
+            });
+        }
+        let noise_color = noise.as_ref().map(|(color, _)| *color);
+        if target.noise > 0.0 && noise_color != target.color {
+            noise = target.color.and_then(|color| {
+                let sink = new_sink(&mut output)?;
+                sink.set_volume(0.0);
+                sink.append(Noise::new(color));
+                Some((color, sink))
+            });
+        }
+        //Sounds that have faded out are stopped.
+        if tick
+            .as_ref()
+            .map_or(false, |sink| !step_volume(sink, target.tick, target.fade))
+        {
+            tick = None;
+        }
+        if noise.as_ref().map_or(false, |(_, sink)| {
+            !step_volume(sink, target.noise, target.fade)
+        }) {
+            noise = None;
+        }
+        sounds.retain(|sink| !sink.empty());
+        if tick.is_none() && noise.is_none() && sounds.is_empty() {
+            output = None;
+        }
+    }
+}
+
+//Opens the audio output if it is not open yet, None if there is no audio device.
+fn new_sink(output: &mut Option<(OutputStream, OutputStreamHandle)>) -> Option<Sink> {
+    if output.is_none() {
+        *output = OutputStream::try_default().ok();
+    }
+    let (_, handle) = output.as_ref()?;
+    Sink::try_new(handle).ok()
+}
+
+//Sounds come in gently either way, fading out is optional. Returns false once the sound is
+//silent and is not to be heard again.
+fn step_volume(sink: &Sink, target: f32, fade: bool) -> bool {
+    let volume = sink.volume();
+    let next = if volume < target {
+        (volume + FADE_PER_STEP).min(target)
+    } else if fade {
+        (volume - FADE_PER_STEP).max(target)
+    } else {
+        target
+    };
+    sink.set_volume(next);
+    next > 0.0 || target > 0.0
+}
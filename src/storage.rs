@@ -0,0 +1,43 @@
+use crate::session::SessionHistory;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+//Name the native app is run under, eframe uses it to decide where the app state is saved. It is
+//still the template's name so that existing settings and history are found, the window title is
+//set by the app itself.
+pub const APP_ID: &str = "eframe template";
+
+//The parts of the saved `TimerApp` that are needed outside of the gui.
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct SavedApp {
+    history: SessionHistory,
+}
+
+//Same location as eframe's own file storage.
+fn saved_state_path() -> Option<PathBuf> {
+    directories_next::ProjectDirs::from("", "", APP_ID)
+        .map(|proj_dirs| proj_dirs.data_dir().join("app.ron"))
+}
+
+//Where the running app listens for commands, see control.rs.
+#[cfg(unix)]
+pub fn control_socket_path() -> Option<PathBuf> {
+    directories_next::ProjectDirs::from("", "", APP_ID)
+        .map(|proj_dirs| proj_dirs.data_dir().join("control.sock"))
+}
+
+//Reads the session history saved by the last run of the app.
+pub fn load_saved_history() -> Result<SessionHistory, String> {
+    let path = saved_state_path().ok_or("Could not find the app's data directory")?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    let key_values: HashMap<String, String> = ron::from_str(&contents)
+        .map_err(|err| format!("Could not parse {}: {}", path.display(), err))?;
+    let app = match key_values.get(eframe::APP_KEY) {
+        Some(app) => ron::from_str::<SavedApp>(app)
+            .map_err(|err| format!("Could not parse the saved app state: {}", err))?,
+        None => SavedApp::default(),
+    };
+    Ok(app.history)
+}
@@ -0,0 +1,162 @@
+//Strict mode keeps a running work phase from being paused, skipped or switched on a whim. Doing
+//so anyway takes a deliberate confirmation, which is recorded with the session.
+use crate::timer::WorkTimes;
+use egui::{Button, Ui, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum Unlock {
+    //Hold a button down for a number of seconds.
+    Hold,
+    //Type a phrase.
+    Phrase,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct StrictMode {
+    pub enabled: bool,
+    pub unlock: Unlock,
+    pub hold_seconds: u32,
+    pub phrase: String,
+}
+
+impl Default for StrictMode {
+    fn default() -> Self {
+        StrictMode {
+            enabled: false,
+            unlock: Unlock::Hold,
+            hold_seconds: 5,
+            phrase: String::from("I am giving up this session"),
+        }
+    }
+}
+
+impl StrictMode {
+    pub fn draw_settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Strict mode for work phases");
+        if !self.enabled {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Override by");
+            ui.radio_value(&mut self.unlock, Unlock::Hold, "holding a button");
+            ui.radio_value(&mut self.unlock, Unlock::Phrase, "typing a phrase");
+        });
+        match self.unlock {
+            Unlock::Hold => {
+                ui.add(egui::Slider::new(&mut self.hold_seconds, 1..=30).text("Seconds to hold"));
+            }
+            Unlock::Phrase => {
+                ui.add(egui::TextEdit::singleline(&mut self.phrase).hint_text("Phrase to type"));
+            }
+        }
+    }
+}
+
+//What the user tried to do to the running work phase.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StrictAction {
+    Pause,
+    Skip,
+    SwitchTo(WorkTimes),
+}
+
+impl std::fmt::Display for StrictAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrictAction::Pause => write!(f, "Pause"),
+            StrictAction::Skip => write!(f, "Skip"),
+            StrictAction::SwitchTo(work_time) => write!(f, "Switch to {}", work_time),
+        }
+    }
+}
+
+pub struct OverrideDialog {
+    pub action: StrictAction,
+    //Start of the work session the action was asked for.
+    pub session: Option<u64>,
+    typed: String,
+    //Input time at which the hold button was pressed.
+    held_since: Option<f64>,
+    //The timer moved on while the dialog was open, so the action was not done.
+    phase_changed: bool,
+}
+
+impl OverrideDialog {
+    pub fn new(action: StrictAction, session: Option<u64>) -> OverrideDialog {
+        OverrideDialog {
+            action,
+            session,
+            typed: String::new(),
+            held_since: None,
+            phase_changed: false,
+        }
+    }
+
+    //Tells the user that the action was dropped, until they close the dialog.
+    pub fn phase_changed(&mut self) {
+        self.phase_changed = true;
+    }
+
+    //Returns Some(true) once the override is confirmed and Some(false) when it is cancelled.
+    pub fn show(&mut self, ctx: &egui::Context, strict: &StrictMode) -> Option<bool> {
+        let mut result = None;
+        egui::Window::new("Strict mode")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                if self.phase_changed {
+                    ui.label(format!(
+                        "The timer moved on while this was open, \"{}\" was not done.",
+                        self.action
+                    ));
+                    if ui.button("OK").clicked() {
+                        result = Some(false);
+                    }
+                    return;
+                }
+                ui.label(format!(
+                    "{} this work phase? The override is recorded with the session.",
+                    self.action
+                ));
+                let confirmed = match strict.unlock {
+                    Unlock::Hold => self.draw_hold(ui, strict.hold_seconds),
+                    Unlock::Phrase => self.draw_phrase(ui, &strict.phrase),
+                };
+                if confirmed {
+                    result = Some(true);
+                }
+                if ui.button("Keep working").clicked() {
+                    result = Some(false);
+                }
+            });
+        result
+    }
+
+    fn draw_hold(&mut self, ui: &mut Ui, seconds: u32) -> bool {
+        let now = ui.input(|i| i.time);
+        let pressed = ui
+            .add(Button::new(format!("Hold for {} s", seconds)))
+            .is_pointer_button_down_on();
+        let progress = if pressed {
+            let held = now - *self.held_since.get_or_insert(now);
+            (held / f64::from(seconds.max(1))) as f32
+        } else {
+            self.held_since = None;
+            0.0
+        };
+        ui.add(egui::ProgressBar::new(progress.min(1.0)));
+        if pressed {
+            ui.ctx().request_repaint();
+        }
+        progress >= 1.0
+    }
+
+    fn draw_phrase(&mut self, ui: &mut Ui, phrase: &str) -> bool {
+        ui.label(format!("Type \"{}\" to go on.", phrase));
+        ui.text_edit_singleline(&mut self.typed);
+        let matches = self.typed.trim() == phrase.trim();
+        ui.add_enabled(matches, Button::new("Confirm")).clicked()
+    }
+}
@@ -0,0 +1,480 @@
+//Tasks kept in todo.txt files or Markdown checklists. Imported tasks remember the file and line
+//they came from, so ticking them off and the pomodoros spent on them can be written back.
+use crate::clock::{self, DateTime};
+use crate::tasks::{Task, TaskSource};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+//Key used in both formats for the number of pomodoros, e.g. "Write report pomodoros:3".
+const POMODORO_KEY: &str = "pomodoros:";
+//Key for the estimated number of pomodoros, e.g. "Write report est:4".
+const ESTIMATE_KEY: &str = "est:";
+//How often (in seconds) watched files are checked for changes.
+const WATCH_INTERVAL: f64 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FileKind {
+    TodoTxt,
+    Markdown,
+}
+
+fn file_kind(path: &str) -> FileKind {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".md") || lower.ends_with(".markdown") {
+        FileKind::Markdown
+    } else {
+        FileKind::TodoTxt
+    }
+}
+
+pub fn read_task_file(path: &str) -> Result<Vec<Task>, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+    let kind = file_kind(path);
+    Ok(text
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let mut task = match kind {
+                FileKind::TodoTxt => parse_todo_txt_line(text)?,
+                FileKind::Markdown => parse_markdown_line(text)?,
+            };
+            task.source = Some(TaskSource {
+                path: path.to_owned(),
+                line,
+            });
+            Some(task)
+        })
+        .collect())
+}
+
+//"x 2026-10-19 (A) 2026-10-01 Write report +Acme @office est:3 pomodoros:2", see
+//https://github.com/todotxt/todo.txt for the format. The first +project becomes the project and
+//every @context a tag.
+pub fn parse_todo_txt_line(line: &str) -> Option<Task> {
+    let mut rest = line.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut task = Task::default();
+    if let Some(done) = rest.strip_prefix("x ") {
+        task.done = true;
+        rest = skip_date(done.trim_start());
+    }
+    if let Some(priority) = parse_priority(rest) {
+        task.priority = Some(priority);
+        rest = rest[3..].trim_start();
+    }
+    rest = skip_date(rest);
+    let mut words = Vec::new();
+    for word in rest.split_whitespace() {
+        if is_project(word) {
+            task.project.get_or_insert_with(|| word[1..].to_owned());
+        } else if is_tag(word, FileKind::TodoTxt) {
+            task.tags.push(word[1..].to_owned());
+        } else if let Some(count) = word.strip_prefix(POMODORO_KEY) {
+            task.pomodoros = count.parse().unwrap_or(0);
+        } else if let Some(estimate) = word.strip_prefix(ESTIMATE_KEY) {
+            task.estimate = estimate.parse().unwrap_or(0);
+        } else {
+            words.push(word);
+        }
+    }
+    task.name = words.join(" ");
+    Some(task)
+}
+
+//"(A) " at the start of a todo.txt line.
+fn parse_priority(text: &str) -> Option<char> {
+    let bytes = text.as_bytes();
+    if bytes.len() >= 4
+        && bytes[0] == b'('
+        && bytes[1].is_ascii_uppercase()
+        && bytes[2] == b')'
+        && bytes[3] == b' '
+    {
+        Some(bytes[1] as char)
+    } else {
+        None
+    }
+}
+
+fn skip_date(text: &str) -> &str {
+    match text.split_once(' ') {
+        Some((date, rest)) if clock::Date::parse(date).is_some() => rest.trim_start(),
+        _ => text,
+    }
+}
+
+//"- [ ] Write report +Acme #writing est:3 pomodoros:2", also with * or + as bullet and [x] for
+//done tasks. As in todo.txt the first +project becomes the project.
+pub fn parse_markdown_line(line: &str) -> Option<Task> {
+    let (done, rest) = split_markdown_checkbox(line)?;
+    let mut task = Task {
+        done,
+        ..Task::default()
+    };
+    let mut words = Vec::new();
+    for word in rest.split_whitespace() {
+        if is_project(word) {
+            task.project.get_or_insert_with(|| word[1..].to_owned());
+        } else if is_tag(word, FileKind::Markdown) {
+            task.tags.push(word[1..].to_owned());
+        } else if let Some(count) = word.strip_prefix(POMODORO_KEY) {
+            task.pomodoros = count.parse().unwrap_or(0);
+        } else if let Some(estimate) = word.strip_prefix(ESTIMATE_KEY) {
+            task.estimate = estimate.parse().unwrap_or(0);
+        } else {
+            words.push(word);
+        }
+    }
+    task.name = words.join(" ");
+    Some(task).filter(|task| !task.name.is_empty())
+}
+
+//Splits "  - [x] text" into whether it is ticked and the text, None for other lines.
+fn split_markdown_checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line.trim_start();
+    let rest = rest
+        .strip_prefix("- ")
+        .or_else(|| rest.strip_prefix("* "))
+        .or_else(|| rest.strip_prefix("+ "))?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    Some((done, rest[3..].trim()))
+}
+
+//Writes the task's done state, pomodoros, estimate, project and tags back into the line it was
+//imported from. Everything else in the file, line endings included, is left as it is.
+pub fn write_back(task: &Task) -> Result<(), String> {
+    edit_line(task, |lines, index, kind| {
+        lines[index].0 = update_line(&lines[index].0, task, kind);
+    })
+}
+
+//Takes a deleted task's line out of its file, so that it does not come back on the next reload.
+pub fn remove_line(task: &Task) -> Result<(), String> {
+    edit_line(task, |lines, index, _| {
+        lines.remove(index);
+    })
+}
+
+//Changes the lines of the file the task was imported from, `index` is the task's line. The line
+//is looked up by the task name in case the file changed since it was read.
+fn edit_line(
+    task: &Task,
+    edit: impl FnOnce(&mut Vec<(String, &str)>, usize, FileKind),
+) -> Result<(), String> {
+    let source = match &task.source {
+        Some(source) => source,
+        None => return Ok(()),
+    };
+    let kind = file_kind(&source.path);
+    let text = std::fs::read_to_string(&source.path)
+        .map_err(|err| format!("Could not read {}: {}", source.path, err))?;
+    let mut lines = split_lines(&text);
+    let parse = |line: &str| match kind {
+        FileKind::TodoTxt => parse_todo_txt_line(line),
+        FileKind::Markdown => parse_markdown_line(line),
+    };
+    let same_task =
+        |(line, _): &(String, &str)| parse(line).map_or(false, |parsed| parsed.name == task.name);
+    let index = if lines.get(source.line).map_or(false, same_task) {
+        source.line
+    } else {
+        lines
+            .iter()
+            .position(same_task)
+            .ok_or_else(|| format!("\"{}\" is no longer in {}", task.name, source.path))?
+    };
+    edit(&mut lines, index, kind);
+    let text_out: String = lines
+        .iter()
+        .flat_map(|(line, ending)| [line.as_str(), ending])
+        .collect();
+    std::fs::write(&source.path, text_out)
+        .map_err(|err| format!("Could not write {}: {}", source.path, err))
+}
+
+//Lines without and with their line ending, which is "\n", "\r\n" or nothing for the last line.
+fn split_lines(text: &str) -> Vec<(String, &str)> {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let content = line.trim_end_matches(['\r', '\n']);
+            (content.to_owned(), &line[content.len()..])
+        })
+        .collect()
+}
+
+fn is_project(word: &str) -> bool {
+    word.len() > 1 && word.starts_with('+')
+}
+
+fn is_tag(word: &str, kind: FileKind) -> bool {
+    match kind {
+        FileKind::TodoTxt => word.len() > 1 && word.starts_with('@'),
+        FileKind::Markdown => word.len() > 1 && word.starts_with('#') && !word.starts_with("##"),
+    }
+}
+
+fn update_line(line: &str, task: &Task, kind: FileKind) -> String {
+    let tag_prefix = match kind {
+        FileKind::TodoTxt => '@',
+        FileKind::Markdown => '#',
+    };
+    let count = |key: &str, count: u32| {
+        Some(format!("{}{}", key, count))
+            .filter(|_| count > 0)
+            .into_iter()
+            .collect::<Vec<String>>()
+    };
+    let line = set_words(
+        line,
+        is_project,
+        &task
+            .project
+            .iter()
+            .map(|p| format!("+{}", p))
+            .collect::<Vec<String>>(),
+    );
+    let line = set_words(
+        &line,
+        |word| is_tag(word, kind),
+        &task
+            .tags
+            .iter()
+            .map(|tag| format!("{}{}", tag_prefix, tag))
+            .collect::<Vec<String>>(),
+    );
+    let line = set_words(
+        &line,
+        |word| word.starts_with(ESTIMATE_KEY),
+        &count(ESTIMATE_KEY, task.estimate),
+    );
+    let line = set_words(
+        &line,
+        |word| word.starts_with(POMODORO_KEY),
+        &count(POMODORO_KEY, task.pomodoros),
+    );
+    match kind {
+        FileKind::TodoTxt => set_todo_txt_done(line, task.done),
+        FileKind::Markdown => set_markdown_done(&line, task.done),
+    }
+}
+
+//Replaces the words picked out by `is_marker` with `words`, where the first of them was or else
+//at the end. The line is kept as it is when they are already the same.
+fn set_words(line: &str, is_marker: impl Fn(&str) -> bool, words: &[String]) -> String {
+    let old: Vec<&str> = line.split(' ').collect();
+    if old.iter().filter(|word| is_marker(word)).eq(words.iter()) {
+        return line.to_owned();
+    }
+    let first = old.iter().position(|word| is_marker(word));
+    let mut new: Vec<&str> = old.into_iter().filter(|word| !is_marker(word)).collect();
+    while new.len() > 1 && new.last().map_or(false, |word| word.is_empty()) {
+        new.pop();
+    }
+    let at = first.map_or(new.len(), |first| first.min(new.len()));
+    new.splice(at..at, words.iter().map(|word| word.as_str()));
+    new.join(" ")
+}
+
+fn set_todo_txt_done(line: String, done: bool) -> String {
+    let was_done = line.starts_with("x ");
+    if done && !was_done {
+        let today = DateTime::local(clock::now_unix()).date;
+        format!("x {} {}", today, line)
+    } else if !done && was_done {
+        skip_date(line[2..].trim_start()).to_owned()
+    } else {
+        line
+    }
+}
+
+fn set_markdown_done(line: &str, done: bool) -> String {
+    let checkbox = line.find('[').unwrap_or(0);
+    format!(
+        "{}[{}]{}",
+        &line[..checkbox],
+        if done { 'x' } else { ' ' },
+        line.get(checkbox + 3..).unwrap_or("")
+    )
+}
+
+//Polls the modification times of the task files, there is no file watching in std.
+#[derive(Default)]
+pub struct TaskFileWatcher {
+    modified: HashMap<String, SystemTime>,
+    last_check: f64,
+}
+
+impl TaskFileWatcher {
+    //Returns the files that changed since they were last seen, at most every WATCH_INTERVAL.
+    pub fn changed_files(&mut self, files: &[String], now: f64) -> Vec<String> {
+        if now - self.last_check < WATCH_INTERVAL {
+            return Vec::new();
+        }
+        self.last_check = now;
+        let mut changed = Vec::new();
+        for path in files {
+            let modified = match std::fs::metadata(path).and_then(|meta| meta.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue,
+            };
+            if self.modified.insert(path.clone(), modified) != Some(modified) {
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    //Remembers the current modification time, so our own writes are not seen as changes.
+    pub fn mark_seen(&mut self, path: &str) {
+        if let Ok(modified) = std::fs::metadata(path).and_then(|meta| meta.modified()) {
+            self.modified.insert(path.to_owned(), modified);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, text: &str) -> String {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn parses_todo_txt() {
+        let task = parse_todo_txt_line(
+            "x 2026-10-19 (A) 2026-10-01 Write report +Acme +Other @office est:3 pomodoros:2",
+        )
+        .unwrap();
+        assert!(task.done);
+        assert_eq!(task.priority, Some('A'));
+        assert_eq!(task.name, "Write report");
+        assert_eq!(task.project.as_deref(), Some("Acme"));
+        assert_eq!(task.tags, vec!["office"]);
+        assert_eq!((task.estimate, task.pomodoros), (3, 2));
+        assert_eq!(parse_todo_txt_line("   "), None);
+        assert_eq!(
+            parse_todo_txt_line("(a) lower + @").unwrap().name,
+            "(a) lower + @"
+        );
+    }
+
+    #[test]
+    fn parses_markdown() {
+        let task = parse_markdown_line("  * [X] Write report +Acme #writing ##no est:x").unwrap();
+        assert!(task.done);
+        assert_eq!(task.name, "Write report ##no");
+        assert_eq!(task.project.as_deref(), Some("Acme"));
+        assert_eq!(task.tags, vec!["writing"]);
+        assert_eq!(task.estimate, 0);
+        for line in ["# Heading", "- [ ]", "- [?] odd", "-[ ] no space", "text"] {
+            assert_eq!(parse_markdown_line(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn unchanged_tasks_keep_their_line() {
+        for (line, kind) in [
+            (
+                "(B) Call +Acme @phone est:2 pomodoros:1 due:soon",
+                FileKind::TodoTxt,
+            ),
+            (
+                "  - [ ] Call +Acme #phone est:2 notes pomodoros:1",
+                FileKind::Markdown,
+            ),
+        ] {
+            let task = match kind {
+                FileKind::TodoTxt => parse_todo_txt_line(line),
+                FileKind::Markdown => parse_markdown_line(line),
+            }
+            .unwrap();
+            assert_eq!(update_line(line, &task, kind), line);
+        }
+    }
+
+    #[test]
+    fn writes_edits_into_the_line() {
+        let line = "(B) Call +Acme @phone est:2 due:soon";
+        let mut task = parse_todo_txt_line(line).unwrap();
+        task.project = Some(String::from("Beta"));
+        task.tags = vec![String::from("office"), String::from("calls")];
+        task.estimate = 0;
+        task.pomodoros = 4;
+        let updated = update_line(line, &task, FileKind::TodoTxt);
+        assert_eq!(
+            updated,
+            "(B) Call +Beta @office @calls due:soon pomodoros:4"
+        );
+        assert_eq!(parse_todo_txt_line(&updated).unwrap(), task);
+
+        let line = "- [ ] Call #phone";
+        let mut task = parse_markdown_line(line).unwrap();
+        task.done = true;
+        task.project = Some(String::from("Acme"));
+        task.tags.clear();
+        task.estimate = 3;
+        let updated = update_line(line, &task, FileKind::Markdown);
+        assert_eq!(updated, "- [x] Call +Acme est:3");
+        assert_eq!(parse_markdown_line(&updated).unwrap(), task);
+    }
+
+    #[test]
+    fn write_back_keeps_line_endings() {
+        let text = "- [ ] First\r\n- [ ] Second #a\r\n\r\nNotes\r\n- [ ] Last";
+        let path = temp_file("tasks.md", text);
+        let mut tasks = read_task_file(&path).unwrap();
+        assert_eq!(tasks.len(), 3);
+        tasks[1].done = true;
+        tasks[1].pomodoros = 2;
+        write_back(&tasks[1]).unwrap();
+        tasks[2].estimate = 1;
+        write_back(&tasks[2]).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            written,
+            "- [ ] First\r\n- [x] Second #a pomodoros:2\r\n\r\nNotes\r\n- [ ] Last est:1"
+        );
+    }
+
+    #[test]
+    fn removes_deleted_tasks_from_the_file() {
+        let path = temp_file("removed.txt", "Write report\r\nCall Bob\r\nPay rent");
+        let tasks = read_task_file(&path).unwrap();
+        remove_line(&tasks[1]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Write report\r\nPay rent"
+        );
+        assert!(remove_line(&tasks[1]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn write_back_finds_moved_lines() {
+        let path = temp_file("todo.txt", "Write report\nCall Bob\n");
+        let mut task = read_task_file(&path).unwrap().remove(1);
+        std::fs::write(&path, "New task\nWrite report\nCall Bob\n").unwrap();
+        task.done = true;
+        write_back(&task).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let today = DateTime::local(clock::now_unix()).date;
+        assert_eq!(
+            written,
+            format!("New task\nWrite report\nx {} Call Bob\n", today)
+        );
+        task.name = String::from("Gone");
+        assert!(write_back(&task).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,452 @@
+use crate::session::SessionRecord;
+use egui::{Color32, Ui};
+use std::cmp::Ordering;
+
+//Projects group tasks and sessions (e.g. per client), tags are free form labels across projects
+//(area, type of work). Both are referred to by name from tasks and session records.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Project {
+    pub name: String,
+    pub color: Color32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Task {
+    pub name: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub done: bool,
+    //'A' is the most important, as in todo.txt.
+    pub priority: Option<char>,
+    //Completed work sessions spent on the task.
+    pub pomodoros: u32,
+    //Pomodoros the task is expected to take, 0 if not estimated.
+    pub estimate: u32,
+    pub source: Option<TaskSource>,
+}
+
+//File and line (counting from 0) an imported task was read from.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct TaskSource {
+    pub path: String,
+    pub line: usize,
+}
+
+//What was done on the tasks screen, to be written back into the task files.
+#[derive(Default)]
+pub struct TaskEdits {
+    //Indices of tasks that were changed, e.g. ticked off or given another project.
+    pub changed: Vec<usize>,
+    pub removed: Option<Task>,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TaskList {
+    projects: Vec<Project>,
+    tasks: Vec<Task>,
+    //todo.txt and Markdown files tasks were imported from.
+    pub files: Vec<String>,
+    //Re-import the files whenever they change on disk.
+    pub watch_files: bool,
+}
+
+//Colors handed out to new projects in turn.
+const PROJECT_COLORS: [Color32; 6] = [
+    Color32::from_rgb(217, 93, 57),
+    Color32::from_rgb(56, 133, 138),
+    Color32::from_rgb(57, 112, 151),
+    Color32::from_rgb(240, 162, 2),
+    Color32::from_rgb(140, 90, 160),
+    Color32::from_rgb(90, 150, 70),
+];
+
+impl TaskList {
+    pub fn projects(&self) -> &Vec<Project> {
+        &self.projects
+    }
+    pub fn tasks(&self) -> &Vec<Task> {
+        &self.tasks
+    }
+    pub fn tasks_mut(&mut self) -> &mut Vec<Task> {
+        &mut self.tasks
+    }
+
+    pub fn project_color(&self, name: &str) -> Option<Color32> {
+        self.projects
+            .iter()
+            .find(|project| project.name == name)
+            .map(|project| project.color)
+    }
+
+    //Adds a project unless one with that name already exists.
+    pub fn add_project(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() || self.projects.iter().any(|project| project.name == name) {
+            return;
+        }
+        self.projects.push(Project {
+            name: name.to_owned(),
+            color: PROJECT_COLORS[self.projects.len() % PROJECT_COLORS.len()],
+        });
+    }
+
+    pub fn add_task(&mut self, task: Task) {
+        if let Some(project) = &task.project {
+            self.add_project(project);
+        }
+        self.tasks.push(task);
+    }
+
+    //Brings the tasks imported from a file up to date with what it contains now. Tasks are
+    //matched by name, so they keep their place in the list, tasks that are no longer in the file
+    //are removed and new ones added at the end.
+    pub fn replace_from_file(&mut self, path: &str, tasks: Vec<Task>) {
+        let from_file = |task: &Task| {
+            task.source
+                .as_ref()
+                .map_or(false, |source| source.path == path)
+        };
+        let mut matched = vec![false; self.tasks.len()];
+        let mut added = Vec::new();
+        for task in tasks {
+            if let Some(project) = &task.project {
+                self.add_project(project);
+            }
+            let existing = self.tasks.iter().enumerate().position(|(index, old)| {
+                !matched[index] && from_file(old) && old.name == task.name
+            });
+            match existing {
+                Some(index) => {
+                    matched[index] = true;
+                    self.tasks[index] = task;
+                }
+                None => added.push(task),
+            }
+        }
+        let mut index = 0;
+        self.tasks.retain(|task| {
+            let keep = matched[index] || !from_file(task);
+            index += 1;
+            keep
+        });
+        self.tasks.extend(added);
+        if !self.files.iter().any(|file| file == path) {
+            self.files.push(path.to_owned());
+        }
+    }
+
+    //Counts a completed work session towards the first open task of that name, returns its index.
+    pub fn count_pomodoro(&mut self, name: &str) -> Option<usize> {
+        let index = self
+            .tasks
+            .iter()
+            .position(|task| !task.done && task.name == name)?;
+        self.tasks[index].pomodoros += 1;
+        Some(index)
+    }
+
+    //Open tasks, most important first.
+    pub fn open_tasks(&self) -> Vec<&Task> {
+        let mut open: Vec<&Task> = self.tasks.iter().filter(|task| !task.done).collect();
+        open.sort_by_key(|task| task.priority.unwrap_or('~'));
+        open
+    }
+
+    //Every tag used by a task or a session, sorted.
+    pub fn all_tags<'a>(&'a self, sessions: &'a [SessionRecord]) -> Vec<&'a str> {
+        let mut tags: Vec<&str> = self
+            .tasks
+            .iter()
+            .flat_map(|task| &task.tags)
+            .chain(sessions.iter().flat_map(|session| &session.tags))
+            .map(|tag| tag.as_str())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    //Removes a task, the indices of changed tasks after it move up by one.
+    pub fn remove(&mut self, index: usize, edits: &mut TaskEdits) {
+        edits.removed = Some(self.tasks.remove(index));
+        edits.changed = std::mem::take(&mut edits.changed)
+            .into_iter()
+            .filter_map(|changed| match changed.cmp(&index) {
+                Ordering::Less => Some(changed),
+                Ordering::Equal => None,
+                Ordering::Greater => Some(changed - 1),
+            })
+            .collect();
+    }
+
+    //The tasks screen: a list of tasks with their project and tags, and the project colors.
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        new_task: &mut String,
+        new_project: &mut String,
+    ) -> TaskEdits {
+        let mut changed = Vec::new();
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(new_task).hint_text("New task"));
+            if ui.button("Add task").clicked() && !new_task.trim().is_empty() {
+                self.add_task(Task {
+                    name: new_task.trim().to_owned(),
+                    ..Task::default()
+                });
+                new_task.clear();
+            }
+        });
+        let project_names: Vec<String> = self
+            .projects
+            .iter()
+            .map(|project| project.name.clone())
+            .collect();
+        let mut removed = None;
+        egui::Grid::new("tasks_grid").striped(true).show(ui, |ui| {
+            for (index, task) in self.tasks.iter_mut().enumerate() {
+                let before = task.clone();
+                ui.checkbox(&mut task.done, "");
+                ui.label(match task.priority {
+                    Some(priority) => format!("({}) {}", priority, task.name),
+                    None => task.name.clone(),
+                });
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} /", task.pomodoros));
+                    ui.add(egui::DragValue::new(&mut task.estimate).clamp_range(0..=50))
+                        .on_hover_text("Estimated pomodoros");
+                    ui.label("🍅");
+                });
+                project_combo_box(
+                    ui,
+                    ("task_project", index),
+                    &mut task.project,
+                    &project_names,
+                    "No project",
+                );
+                let mut tags = task.tags.join(", ");
+                if ui
+                    .add(egui::TextEdit::singleline(&mut tags).hint_text("Tags"))
+                    .changed()
+                {
+                    task.tags = parse_tags(&tags);
+                }
+                if ui.button("Delete").clicked() {
+                    removed = Some(index);
+                }
+                if *task != before {
+                    changed.push(index);
+                }
+                ui.end_row();
+            }
+        });
+        let mut edits = TaskEdits {
+            changed,
+            removed: None,
+        };
+        if let Some(index) = removed {
+            self.remove(index, &mut edits);
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(new_project).hint_text("New project"));
+            if ui.button("Add project").clicked() {
+                self.add_project(new_project);
+                new_project.clear();
+            }
+        });
+        for project in &mut self.projects {
+            ui.horizontal(|ui| {
+                egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    &mut project.color,
+                    egui::color_picker::Alpha::Opaque,
+                );
+                ui.label(&project.name);
+            });
+        }
+        edits
+    }
+}
+
+//"client, writing" -> ["client", "writing"]
+pub fn parse_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|tag| tag.trim().trim_start_matches('#').to_owned())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+//Combo box to pick one of the projects, `none_label` is shown for None.
+pub fn project_combo_box(
+    ui: &mut Ui,
+    id: impl std::hash::Hash,
+    project: &mut Option<String>,
+    project_names: &[String],
+    none_label: &str,
+) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(project.as_deref().unwrap_or(none_label))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(project, None, none_label);
+            for name in project_names {
+                ui.selectable_value(project, Some(name.clone()), name);
+            }
+        });
+}
+
+//Limits the history and statistics to one project and/or tag.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SessionFilter {
+    pub project: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, session: &SessionRecord) -> bool {
+        self.project
+            .as_ref()
+            .map_or(true, |project| session.project.as_ref() == Some(project))
+            && self
+                .tag
+                .as_ref()
+                .map_or(true, |tag| session.tags.contains(tag))
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui, tasks: &TaskList, sessions: &[SessionRecord]) {
+        let project_names: Vec<String> = tasks
+            .projects()
+            .iter()
+            .map(|project| project.name.clone())
+            .collect();
+        ui.horizontal(|ui| {
+            project_combo_box(
+                ui,
+                "filter_project",
+                &mut self.project,
+                &project_names,
+                "Any project",
+            );
+            egui::ComboBox::from_id_source("filter_tag")
+                .selected_text(self.tag.as_deref().unwrap_or("Any tag"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.tag, None, "Any tag");
+                    for tag in tasks.all_tags(sessions) {
+                        ui.selectable_value(&mut self.tag, Some(tag.to_owned()), tag);
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::WorkTimes;
+
+    fn task(name: &str, path: &str, line: usize) -> Task {
+        Task {
+            name: name.to_owned(),
+            source: Some(TaskSource {
+                path: path.to_owned(),
+                line,
+            }),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn reloading_a_file_keeps_tasks_in_place() {
+        let mut list = TaskList::default();
+        list.add_task(Task {
+            name: String::from("Own task"),
+            ..Task::default()
+        });
+        list.replace_from_file(
+            "a.txt",
+            vec![task("One", "a.txt", 0), task("Two", "a.txt", 1)],
+        );
+        list.add_task(task("Other file", "b.txt", 0));
+        let mut two = task("Two", "a.txt", 0);
+        two.done = true;
+        two.project = Some(String::from("Acme"));
+        list.replace_from_file("a.txt", vec![two.clone(), task("Three", "a.txt", 1)]);
+        let names: Vec<&str> = list.tasks().iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, vec!["Own task", "Two", "Other file", "Three"]);
+        assert_eq!(list.tasks()[1], two);
+        assert!(list.project_color("Acme").is_some());
+        assert_eq!(list.files, vec!["a.txt"]);
+    }
+
+    #[test]
+    fn counts_pomodoros_on_open_tasks() {
+        let mut list = TaskList::default();
+        let mut done = task("Same", "a.txt", 0);
+        done.done = true;
+        list.add_task(done);
+        list.add_task(task("Same", "a.txt", 1));
+        assert_eq!(list.count_pomodoro("Same"), Some(1));
+        assert_eq!(list.count_pomodoro("Missing"), None);
+        assert_eq!(list.tasks()[1].pomodoros, 1);
+    }
+
+    #[test]
+    fn removing_keeps_later_edits() {
+        let mut list = TaskList::default();
+        for name in ["A", "B", "C", "D"] {
+            list.add_task(task(name, "a.txt", 0));
+        }
+        let mut edits = TaskEdits {
+            changed: vec![0, 1, 3],
+            removed: None,
+        };
+        list.remove(1, &mut edits);
+        assert_eq!(edits.changed, vec![0, 2]);
+        assert_eq!(edits.removed.unwrap().name, "B");
+        assert_eq!(list.tasks()[2].name, "D");
+    }
+
+    #[test]
+    fn projects_get_colors_and_filter_sessions() {
+        let mut list = TaskList::default();
+        list.add_project(" Acme ");
+        list.add_project("Acme");
+        list.add_project("");
+        list.add_task(Task {
+            name: String::from("Call"),
+            project: Some(String::from("Beta")),
+            tags: vec![String::from("phone")],
+            ..Task::default()
+        });
+        let names: Vec<&str> = list
+            .projects()
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Acme", "Beta"]);
+        assert_ne!(list.project_color("Acme"), list.project_color("Beta"));
+
+        let mut session = SessionRecord::new(WorkTimes::Work, None, 0);
+        session.project = Some(String::from("Beta"));
+        session.tags = vec![String::from("phone"), String::from("client")];
+        assert_eq!(list.all_tags(&[session.clone()]), vec!["client", "phone"]);
+        let filter = |project: Option<&str>, tag: Option<&str>| SessionFilter {
+            project: project.map(str::to_owned),
+            tag: tag.map(str::to_owned),
+        };
+        assert!(filter(None, None).matches(&session));
+        assert!(filter(Some("Beta"), Some("client")).matches(&session));
+        assert!(!filter(Some("Acme"), None).matches(&session));
+        assert!(!filter(None, Some("email")).matches(&session));
+    }
+
+    #[test]
+    fn parses_tags() {
+        assert_eq!(
+            parse_tags(" client, #writing,, "),
+            vec!["client", "writing"]
+        );
+    }
+}
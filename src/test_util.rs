@@ -0,0 +1,28 @@
+//Fixtures shared by the unit tests.
+use crate::clock::{self, Date};
+use crate::session::SessionRecord;
+use crate::timer::WorkTimes;
+use std::time::Duration;
+
+//A monday.
+pub const DAY: Date = Date {
+    year: 2026,
+    month: 10,
+    day: 19,
+};
+
+//Local time at `hour` o'clock, `day` days after DAY.
+pub fn local_time(day: i64, hour: u32) -> u64 {
+    clock::local_to_unix(DAY.add_days(day), hour * 3600)
+}
+
+//A completed work session of `minutes` starting at `local_time(day, hour)`.
+pub fn work_session(day: i64, hour: u32, minutes: u64) -> SessionRecord {
+    let start = local_time(day, hour);
+    SessionRecord {
+        end: start + minutes * 60,
+        duration: Duration::from_secs(minutes * 60),
+        completed: true,
+        ..SessionRecord::new(WorkTimes::Work, None, start)
+    }
+}
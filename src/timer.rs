@@ -1,10 +1,8 @@
-use crate::app::Setting;
-use rodio;
-use rodio::source::Source;
+use crate::app::{EditableSettings, Setting};
+use crate::clock;
+use crate::session::{IdleGap, Interruption, PauseInterval, SessionRecord, StrictOverride};
+use crate::tasks::Task;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::thread;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -15,27 +13,82 @@ pub struct TimerData {
     timer_state: TimerState,
     #[serde(skip)]
     work_time: WorkTimes,
+    #[serde(skip)]
+    task: String,
+    //Project and tags of the next session, set by picking a task from the task list.
+    #[serde(skip)]
+    project: Option<String>,
+    #[serde(skip)]
+    tags: Vec<String>,
+    //Length of the running phase, taken from the settings when it starts and extended by the user.
+    #[serde(skip)]
+    target: Duration,
+    //The running work phase is a Flowtime one, which counts up and has no target.
+    #[serde(skip)]
+    open_ended: bool,
+    //Break length suggested after the last Flowtime work phase.
+    #[serde(skip)]
+    next_break: Option<Duration>,
+    //Every how many work phases the break is a long one (0 is never), taken from the settings
+    //when a phase starts, and the work phases completed since the last long break.
+    #[serde(skip)]
+    long_break_every: u32,
+    #[serde(skip)]
+    work_since_long_break: u32,
+    //The warning before the end of the running phase has been given.
+    #[serde(skip)]
+    warned: bool,
+    #[serde(skip)]
+    active_session: Option<SessionRecord>,
+    //Sessions that ended since the app last collected them with `take_finished_sessions`.
+    #[serde(skip)]
+    finished_sessions: Vec<SessionRecord>,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TimerState {
     Started(Instant),
     Paused(Duration),
+    //The phase ran out at the given moment and the timer counts up until the user acknowledges.
+    Overtime(Instant),
+    //Open-ended Flowtime work counting up from the given moment.
+    Flowing(Instant),
     Done,
 }
 
-#[derive(PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
 pub enum WorkTimes {
     Work,
     Short,
     Long,
 }
 
+impl std::fmt::Display for WorkTimes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkTimes::Work => write!(f, "Work"),
+            WorkTimes::Short => write!(f, "Short break"),
+            WorkTimes::Long => write!(f, "Long break"),
+        }
+    }
+}
+
 impl Default for TimerData {
     fn default() -> Self {
         Self {
             timer_state: TimerState::Done,
             work_time: WorkTimes::Work,
+            task: String::new(),
+            project: None,
+            tags: Vec::new(),
+            target: Duration::from_secs(0),
+            open_ended: false,
+            next_break: None,
+            long_break_every: 0,
+            work_since_long_break: 0,
+            warned: false,
+            active_session: None,
+            finished_sessions: Vec::new(),
         }
     }
 }
@@ -55,6 +108,314 @@ impl TimerData {
         &mut self.work_time
     }
 
+    //What the next session is spent on, empty for no particular task.
+    pub fn task(&self) -> &str {
+        &self.task
+    }
+    pub fn task_mut(&mut self) -> &mut String {
+        &mut self.task
+    }
+
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+    pub fn is_open_ended(&self) -> bool {
+        self.open_ended
+    }
+    pub fn work_since_long_break(&self) -> u32 {
+        self.work_since_long_break
+    }
+    //Time left in a running (or paused) phase, None when there is none or it counts up.
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.timer_state {
+            TimerState::Started(_) | TimerState::Paused(_) => {
+                Some(self.target.saturating_sub(self.elapsed()))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn project_mut(&mut self) -> &mut Option<String> {
+        &mut self.project
+    }
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+    pub fn clear_tags(&mut self) {
+        self.tags.clear();
+    }
+    pub fn select_task(&mut self, task: &Task) {
+        self.task = task.name.clone();
+        self.project = task.project.clone();
+        self.tags = task.tags.clone();
+    }
+
+    //Time counted down so far in the current phase.
+    pub fn elapsed(&self) -> Duration {
+        match self.timer_state {
+            TimerState::Started(time_stamp) | TimerState::Flowing(time_stamp) => {
+                time_stamp.elapsed()
+            }
+            TimerState::Paused(paused_time) => paused_time,
+            TimerState::Overtime(_) => self.target,
+            TimerState::Done => Duration::from_secs(0),
+        }
+    }
+
+    pub fn active_session(&self) -> Option<&SessionRecord> {
+        self.active_session.as_ref()
+    }
+
+    //Records that strict mode was overridden during the running session.
+    pub fn log_override(&mut self, strict_override: StrictOverride) {
+        if let Some(session) = &mut self.active_session {
+            session.overrides.push(strict_override);
+        }
+    }
+
+    //Records the break activity that was done, None clears it. Only breaks have activities.
+    pub fn set_break_activity(&mut self, activity: Option<String>) {
+        if self.work_time == WorkTimes::Work {
+            return;
+        }
+        if let Some(session) = &mut self.active_session {
+            session.activity = activity;
+        }
+    }
+
+    //Interruptions are only tracked during work.
+    pub fn log_interruption(&mut self, interruption: Interruption) {
+        if self.work_time != WorkTimes::Work {
+            return;
+        }
+        if let Some(session) = &mut self.active_session {
+            session.interruptions.push(interruption);
+        }
+    }
+
+    pub fn take_finished_sessions(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.finished_sessions)
+    }
+
+    fn begin_pause(&mut self, reason: Option<String>) {
+        if let Some(session) = &mut self.active_session {
+            let now = clock::now_unix();
+            session.pauses.push(PauseInterval {
+                start: now,
+                end: now,
+                reason,
+            });
+        }
+    }
+
+    fn end_pause(&mut self) {
+        if let Some(pause) = self
+            .active_session
+            .as_mut()
+            .and_then(|session| session.pauses.last_mut())
+        {
+            pause.end = clock::now_unix();
+        }
+    }
+
+    //Gives the ongoing pause a reason, None clears it.
+    pub fn set_pause_reason(&mut self, reason: Option<String>) {
+        if let TimerState::Paused(_) = self.timer_state {
+            if let Some(pause) = self
+                .active_session
+                .as_mut()
+                .and_then(|session| session.pauses.last_mut())
+            {
+                pause.reason = reason;
+            }
+        }
+    }
+
+    //Reason of the ongoing pause, if any.
+    pub fn pause_reason(&self) -> Option<&str> {
+        match self.timer_state {
+            TimerState::Paused(_) => self
+                .active_session
+                .as_ref()
+                .and_then(|session| session.pauses.last())
+                .and_then(|pause| pause.reason.as_deref()),
+            _ => None,
+        }
+    }
+
+    fn finish_session(&mut self, completed: bool) {
+        if let TimerState::Paused(_) = self.timer_state {
+            self.end_pause();
+        }
+        if let Some(mut session) = self.active_session.take() {
+            session.end = clock::now_unix();
+            session.duration = self.elapsed();
+            session.completed = completed;
+            self.finished_sessions.push(session);
+        }
+    }
+
+    //Ends the current phase as completed and moves on to the next one.
+    fn finish_phase(&mut self) {
+        self.finish_session(true);
+        self.timer_state = TimerState::Done;
+        self.next_break = None;
+        match self.work_time {
+            WorkTimes::Work => {
+                self.work_since_long_break += 1;
+                self.work_time = if self.long_break_every > 0
+                    && self.work_since_long_break >= self.long_break_every
+                {
+                    WorkTimes::Long
+                } else {
+                    WorkTimes::Short
+                };
+            }
+            WorkTimes::Long => {
+                self.work_since_long_break = 0;
+                self.work_time = WorkTimes::Work;
+            }
+            WorkTimes::Short => self.work_time = WorkTimes::Work,
+        }
+    }
+
+    //Ends the overtime, which is logged to the session, and moves on to the next phase.
+    pub fn acknowledge_overtime(&mut self) {
+        if let TimerState::Overtime(since) = self.timer_state {
+            if let Some(session) = &mut self.active_session {
+                session.overtime += since.elapsed();
+            }
+            self.finish_phase();
+        }
+    }
+
+    //Stops the current phase without completing it, the session is recorded as not completed.
+    //A phase in overtime has already been completed.
+    pub fn stop(&mut self) {
+        if let TimerState::Overtime(_) = self.timer_state {
+            self.acknowledge_overtime();
+        } else if self.timer_state != TimerState::Done {
+            self.finish_session(false);
+            self.timer_state = TimerState::Done;
+        }
+    }
+
+    pub fn set_work_time(&mut self, work_time: WorkTimes) {
+        self.stop();
+        self.open_ended = false;
+        self.next_break = None;
+        self.work_time = work_time;
+    }
+
+    //Ends an open-ended Flowtime work phase and sets up a break that fits how long it lasted.
+    pub fn end_flow(&mut self, settings: &Setting) {
+        if !self.open_ended || self.timer_state == TimerState::Done {
+            return;
+        }
+        let worked = self.elapsed();
+        self.finish_session(true);
+        self.timer_state = TimerState::Done;
+        self.open_ended = false;
+        self.work_time = WorkTimes::Short;
+        self.next_break = Some(settings.flowtime_breaks().break_for(worked));
+    }
+
+    //Pauses a started timer because the user has been idle, returns how much of the idle time
+    //had already been counted down.
+    pub fn auto_pause(&mut self, idle_for: Duration) -> Duration {
+        match self.timer_state {
+            TimerState::Started(time_stamp) | TimerState::Flowing(time_stamp) => {
+                let elapsed = time_stamp.elapsed();
+                self.timer_state = TimerState::Paused(elapsed);
+                self.begin_pause(Some(String::from("Idle")));
+                idle_for.min(elapsed)
+            }
+            _ => Duration::from_secs(0),
+        }
+    }
+
+    //Records an idle stretch in the current session, discarded idle time is taken off the
+    //paused timer so it does not count as focus time.
+    pub fn resolve_idle(&mut self, gap: IdleGap, counted: Duration) {
+        if !gap.kept {
+            if let TimerState::Paused(paused_time) = self.timer_state {
+                self.timer_state = TimerState::Paused(paused_time.saturating_sub(counted));
+            }
+        }
+        if let Some(session) = &mut self.active_session {
+            session.idle_gaps.push(gap);
+        }
+    }
+
+    //Adds time to a started or paused phase without restarting it.
+    pub fn extend(&mut self, by: Duration) {
+        if self.timer_state == TimerState::Done || self.open_ended {
+            return;
+        }
+        //The overtime so far is logged and the extension counts from now.
+        if let TimerState::Overtime(since) = self.timer_state {
+            if let Some(session) = &mut self.active_session {
+                session.overtime += since.elapsed();
+            }
+            self.timer_state = TimerState::Started(Instant::now() - self.target);
+        }
+        self.target += by;
+        self.warned = false;
+        if let Some(session) = &mut self.active_session {
+            session.extensions.push(by);
+        }
+    }
+
+    //Lets a running or paused phase end after `left`, e.g. before a meeting starts.
+    pub fn shorten_to(&mut self, left: Duration) {
+        if matches!(
+            self.timer_state,
+            TimerState::Started(_) | TimerState::Paused(_)
+        ) {
+            self.target = self.target.min(self.elapsed() + left);
+        }
+    }
+
+    //Starts the timer when done, pauses it when started and resumes it when paused.
+    pub fn toggle_pause(&mut self, settings: &Setting) {
+        if self.timer_state == TimerState::Done {
+            self.long_break_every = settings.long_break_every();
+            self.open_ended = settings.flowtime() && self.work_time == WorkTimes::Work;
+            self.target = match self.next_break {
+                Some(next_break) if self.work_time != WorkTimes::Work => next_break,
+                _ => TimerData::get_work_time(&self.work_time, settings.work_time_settings()),
+            };
+            let task = Some(self.task.trim().to_owned()).filter(|task| !task.is_empty());
+            self.warned = false;
+            let mut session = SessionRecord::new(self.work_time, task, clock::now_unix());
+            session.project = self.project.clone();
+            session.tags = self.tags.clone();
+            self.active_session = Some(session);
+        }
+        let was_paused = matches!(self.timer_state, TimerState::Paused(_));
+        self.timer_state = match self.timer_state {
+            TimerState::Done if self.open_ended => TimerState::Flowing(Instant::now()),
+            TimerState::Done => TimerState::Started(Instant::now()),
+            TimerState::Started(started_time) | TimerState::Flowing(started_time) => {
+                TimerState::Paused(started_time.elapsed())
+            }
+            TimerState::Paused(paused_time) if self.open_ended => {
+                TimerState::Flowing(Instant::now() - paused_time)
+            }
+            TimerState::Paused(paused_time) => TimerState::Started(Instant::now() - paused_time),
+            //Overtime is not paused, it is ended.
+            TimerState::Overtime(_) => {
+                self.acknowledge_overtime();
+                return;
+            }
+        };
+        if let TimerState::Paused(_) = self.timer_state {
+            self.begin_pause(None);
+        } else if was_paused {
+            self.end_pause();
+        }
+    }
+
     //Converts a duration into a string of the form "XX:XX",
     //if duration is more than an hour, this will bug!
     pub fn dur_as_minutes(dur: &Duration) -> String {
@@ -120,107 +481,340 @@ impl TimerData {
     pub fn calculate_timer_text(&self, settings: &Setting) -> String {
         match self.timer_state {
             TimerState::Started(time_stamp) => {
-                format!(
-                    "{}",
-                    TimerData::dur_as_minutes(
-                        &(TimerData::get_work_time(
-                            &self.work_time,
-                            &settings.work_time_settings()
-                        )
-                        .checked_sub(time_stamp.elapsed())
-                        .unwrap_or(Duration::from_secs(0)))
-                    )
-                )
+                TimerData::dur_as_minutes(&self.target.saturating_sub(time_stamp.elapsed()))
             }
             TimerState::Done => {
-                format!(
-                    "{}",
-                    TimerData::dur_as_minutes(&TimerData::get_work_time(
-                        &self.work_time,
-                        &settings.work_time_settings()
-                    ))
-                )
+                let next_phase = match self.next_break {
+                    Some(next_break) if self.work_time != WorkTimes::Work => next_break,
+                    _ if settings.flowtime() && self.work_time == WorkTimes::Work => {
+                        Duration::from_secs(0)
+                    }
+                    _ => TimerData::get_work_time(&self.work_time, settings.work_time_settings()),
+                };
+                TimerData::dur_as_minutes(&next_phase)
+            }
+            TimerState::Flowing(time_stamp) => TimerData::dur_as_minutes(&time_stamp.elapsed()),
+            TimerState::Paused(paused_time) if self.open_ended => {
+                TimerData::dur_as_minutes(&paused_time)
             }
             TimerState::Paused(paused_time) => {
-                format!(
-                    "{}",
-                    TimerData::dur_as_minutes(
-                        &(TimerData::get_work_time(
-                            &self.work_time,
-                            &settings.work_time_settings()
-                        ) - paused_time)
-                    )
-                )
+                TimerData::dur_as_minutes(&self.target.saturating_sub(paused_time))
+            }
+            TimerState::Overtime(since) => {
+                format!("+{}", TimerData::dur_as_minutes(&since.elapsed()).trim())
             }
         }
     }
-    pub fn load_editable_settings(settings: &Setting) -> Vec<String> {
+    pub fn load_editable_settings(settings: &Setting) -> EditableSettings {
         let worktimes_map = &settings.work_time_settings();
-        let mut editable_strings: Vec<String> = Vec::new();
-        editable_strings.push(TimerData::dur_as_minutes(
-            worktimes_map.get(&WorkTimes::Work).unwrap(),
-        ));
-        editable_strings.push(TimerData::dur_as_minutes(
-            worktimes_map.get(&WorkTimes::Long).unwrap(),
-        ));
-        editable_strings.push(TimerData::dur_as_minutes(
-            worktimes_map.get(&WorkTimes::Short).unwrap(),
-        ));
-        editable_strings
-    }
-
-    pub fn update(&mut self, settings: &Setting) -> Result<(), AlertPlayingError> {
-        match self.timer_state {
-            TimerState::Done => return Ok(()),
-            TimerState::Paused(_) => Ok(()),
-            TimerState::Started(time_stamp) => {
-                let time =
-                    TimerData::get_work_time(&self.work_time, &settings.work_time_settings())
-                        .checked_sub(time_stamp.elapsed());
-                if time == None {
-                    self.timer_state = TimerState::Done;
-                    let res = TimerData::play_alert(settings.alert_sound_setting());
-                    match self.work_time {
-                        WorkTimes::Work => self.work_time = WorkTimes::Short,
-                        _ => self.work_time = WorkTimes::Work,
-                    }
-                    return res;
-                } else {
-                    return Ok(());
-                }
-            }
+        EditableSettings {
+            work: TimerData::dur_as_minutes(worktimes_map.get(&WorkTimes::Work).unwrap()),
+            long_break: TimerData::dur_as_minutes(worktimes_map.get(&WorkTimes::Long).unwrap()),
+            short_break: TimerData::dur_as_minutes(worktimes_map.get(&WorkTimes::Short).unwrap()),
+            flowtime_table: settings.flowtime_breaks().table_text(),
+            pause_reasons: settings.pause_reasons().join(", "),
+            working_hours: std::array::from_fn(|weekday| {
+                settings.working_hours().day_text(weekday)
+            }),
+            short_break_activities: settings.break_activities(WorkTimes::Short).join(", "),
+            long_break_activities: settings.break_activities(WorkTimes::Long).join(", "),
         }
     }
-    pub fn play_alert(audio_path: &String) -> Result<(), AlertPlayingError> {
-        let path_clone = audio_path.clone();
-        thread::spawn(move || {
-            let (_stream, stream_handle) = match rodio::OutputStream::try_default() {
-                Ok((output_stream, stream_handle)) => Some((output_stream, stream_handle)),
-                Err(_) => return Err(AlertPlayingError),
+
+    //Moves a started phase on when its time is up. Returns what happened, so the app can play
+    //sounds and show it.
+    pub fn update(&mut self, settings: &Setting) -> Option<TimerEvent> {
+        let time_stamp = match self.timer_state {
+            TimerState::Started(time_stamp) => time_stamp,
+            _ => return None,
+        };
+        let elapsed = time_stamp.elapsed();
+        if elapsed >= self.target {
+            if settings.overtime() {
+                self.timer_state = TimerState::Overtime(time_stamp + self.target);
+            } else {
+                self.finish_phase();
             }
-            .unwrap();
-            //not hanlding errors?? Cringe!
-            let file = BufReader::new(
-                match File::open(path_clone) {
-                    Ok(file) => Some(file),
-                    Err(_) => return Err(AlertPlayingError),
-                }
-                .unwrap(),
-            );
-            let source = match rodio::Decoder::new(file) {
-                Ok(source) => Some(source),
-                Err(_) => return Err(AlertPlayingError),
-            }
-            .unwrap();
-            //If step in thread errors... too bad I won't bother with errors from multithreading.
-            stream_handle
-                .play_raw(source.convert_samples())
-                .or(Err(AlertPlayingError));
-            thread::sleep(Duration::from_secs(5));
-            Ok(())
-        });
-        Ok(())
+            return Some(TimerEvent::Ended);
+        }
+        let warning = settings.phase_warning();
+        let before = Duration::from_secs(warning.before);
+        if !self.warned
+            && warning.applies_to(self.work_time)
+            && self.target > before
+            && elapsed + before >= self.target
+        {
+            self.warned = true;
+            return Some(TimerEvent::Warning);
+        }
+        None
+    }
+}
+
+//Something that happened while the timer was updated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimerEvent {
+    //The running phase is about to end, see PhaseWarning.
+    Warning,
+    //The running phase reached its end.
+    Ended,
+}
+
+//A heads-up shortly before a phase ends, to wrap up a thought: a softer sound and/or a flashing
+//timer.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PhaseWarning {
+    //Seconds before the end, 0 turns the warning off.
+    pub before: u64,
+    //Also warn before breaks end, not only work phases.
+    pub breaks: bool,
+    pub sound: bool,
+    pub volume: f32,
+    pub flash: bool,
+}
+
+impl Default for PhaseWarning {
+    fn default() -> Self {
+        PhaseWarning {
+            before: 60,
+            breaks: false,
+            sound: true,
+            volume: 0.3,
+            flash: true,
+        }
+    }
+}
+
+impl PhaseWarning {
+    pub fn applies_to(&self, work_time: WorkTimes) -> bool {
+        self.before > 0 && (work_time == WorkTimes::Work || self.breaks)
+    }
+}
+
+//How long the break after a Flowtime work phase is: either the work time divided by a ratio or
+//looked up in a table of (work minutes, break minutes) rows sorted by work minutes.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FlowtimeBreaks {
+    pub use_table: bool,
+    pub ratio: f32,
+    pub table: Vec<(u64, u64)>,
+}
+
+impl Default for FlowtimeBreaks {
+    fn default() -> Self {
+        FlowtimeBreaks {
+            use_table: true,
+            ratio: 5.0,
+            table: vec![(25, 5), (50, 8), (90, 15)],
+        }
+    }
+}
+
+impl FlowtimeBreaks {
+    //The break of the first row the work fits into, anything longer than the last row gets its
+    //break.
+    pub fn break_for(&self, worked: Duration) -> Duration {
+        if self.use_table {
+            let worked_minutes = worked.as_secs() / 60;
+            let row = self
+                .table
+                .iter()
+                .find(|(work, _)| worked_minutes <= *work)
+                .or_else(|| self.table.last());
+            Duration::from_secs(row.map_or(0, |(_, rest)| rest * 60))
+        } else {
+            Duration::from_secs_f32(worked.as_secs_f32() / self.ratio.max(1.0))
+        }
+    }
+
+    //"25=5, 50=8, 90=15"
+    pub fn table_text(&self) -> String {
+        self.table
+            .iter()
+            .map(|(work, rest)| format!("{}={}", work, rest))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    pub fn parse_table(text: &str) -> Option<Vec<(u64, u64)>> {
+        let mut table = text
+            .split(',')
+            .filter(|row| !row.trim().is_empty())
+            .map(|row| {
+                let (work, rest) = row.split_once('=')?;
+                Some((work.trim().parse().ok()?, rest.trim().parse().ok()?))
+            })
+            .collect::<Option<Vec<(u64, u64)>>>()?;
+        if table.is_empty() {
+            return None;
+        }
+        table.sort();
+        Some(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phases(long_break_every: u32, count: usize) -> Vec<WorkTimes> {
+        let mut timer = TimerData {
+            long_break_every,
+            ..TimerData::default()
+        };
+        (0..count)
+            .map(|_| {
+                timer.finish_phase();
+                *timer.work_time()
+            })
+            .collect()
+    }
+
+    //A started work phase that ran out `overtime` ago.
+    fn in_overtime(overtime: Duration) -> TimerData {
+        let mut timer = TimerData::default();
+        timer.toggle_pause(&Setting::default());
+        timer.target = Duration::from_secs(60);
+        timer.timer_state = TimerState::Overtime(Instant::now() - overtime);
+        timer
+    }
+
+    #[test]
+    fn extending_overtime_counts_from_now() {
+        let mut timer = in_overtime(Duration::from_secs(120));
+        timer.extend(Duration::from_secs(300));
+        assert_eq!(timer.target(), Duration::from_secs(360));
+        let remaining = timer.remaining().unwrap();
+        assert!(remaining > Duration::from_secs(299) && remaining <= Duration::from_secs(300));
+        timer.stop();
+        let session = &timer.take_finished_sessions()[0];
+        assert!(session.overtime >= Duration::from_secs(120));
+        assert!(session.overtime < Duration::from_secs(121));
+        assert_eq!(session.extensions, vec![Duration::from_secs(300)]);
+    }
+
+    #[test]
+    fn pausing_overtime_ends_the_phase() {
+        let mut timer = in_overtime(Duration::from_secs(5));
+        timer.toggle_pause(&Setting::default());
+        assert!(timer.timer_state() == &TimerState::Done);
+        assert_eq!(timer.work_time(), &WorkTimes::Short);
+        let session = &timer.take_finished_sessions()[0];
+        assert!(session.completed);
+        assert!(session.overtime >= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn warns_once_before_the_end() {
+        let settings = Setting::default();
+        let mut timer = TimerData::default();
+        timer.toggle_pause(&settings);
+        timer.target = Duration::from_secs(120);
+        let started = |elapsed| TimerState::Started(Instant::now() - Duration::from_secs(elapsed));
+        timer.timer_state = started(30);
+        assert_eq!(timer.update(&settings), None);
+        timer.timer_state = started(61);
+        assert_eq!(timer.update(&settings), Some(TimerEvent::Warning));
+        assert_eq!(timer.update(&settings), None);
+        timer.timer_state = started(120);
+        assert_eq!(timer.update(&settings), Some(TimerEvent::Ended));
+        assert_eq!(timer.update(&settings), None);
+    }
+
+    #[test]
+    fn toggles_between_started_and_paused() {
+        let settings = Setting::default();
+        let mut timer = TimerData::default();
+        timer.toggle_pause(&settings);
+        assert!(matches!(timer.timer_state(), TimerState::Started(_)));
+        timer.timer_state = TimerState::Started(Instant::now() - Duration::from_secs(90));
+        timer.toggle_pause(&settings);
+        let paused = match timer.timer_state() {
+            TimerState::Paused(paused) => *paused,
+            _ => panic!("not paused"),
+        };
+        assert!(paused >= Duration::from_secs(90));
+        timer.toggle_pause(&settings);
+        assert!(timer.elapsed() >= Duration::from_secs(90));
+        assert_eq!(timer.active_session().unwrap().pauses.len(), 1);
+    }
+
+    #[test]
+    fn discarded_idle_time_is_taken_off() {
+        let mut timer = TimerData::default();
+        timer.toggle_pause(&Setting::default());
+        timer.timer_state = TimerState::Started(Instant::now() - Duration::from_secs(600));
+        let counted = timer.auto_pause(Duration::from_secs(300));
+        assert_eq!(counted, Duration::from_secs(300));
+        let gap = |kept| IdleGap {
+            start: 0,
+            duration: Duration::from_secs(300),
+            kept,
+        };
+        timer.resolve_idle(gap(false), counted);
+        let left = timer.elapsed();
+        assert!(left >= Duration::from_secs(300) && left < Duration::from_secs(301));
+        let session = timer.active_session().unwrap();
+        assert_eq!(session.idle_gaps, vec![gap(false)]);
+        assert_eq!(session.pauses[0].reason.as_deref(), Some("Idle"));
+        //A paused timer is not paused again, and kept idle time stays counted.
+        assert_eq!(timer.auto_pause(Duration::from_secs(300)), Duration::ZERO);
+        timer.resolve_idle(gap(true), Duration::from_secs(100));
+        assert_eq!(timer.elapsed(), left);
+    }
+
+    #[test]
+    fn flowtime_breaks_fit_the_work() {
+        let minutes = |minutes: u64| Duration::from_secs(minutes * 60);
+        let table = FlowtimeBreaks::default();
+        assert_eq!(table.break_for(minutes(10)), minutes(5));
+        assert_eq!(table.break_for(minutes(50)), minutes(8));
+        assert_eq!(table.break_for(minutes(200)), minutes(15));
+        let ratio = FlowtimeBreaks {
+            use_table: false,
+            ..FlowtimeBreaks::default()
+        };
+        assert_eq!(ratio.break_for(minutes(40)), minutes(8));
+        assert_eq!(
+            FlowtimeBreaks::parse_table(&table.table_text()),
+            Some(table.table)
+        );
+        assert_eq!(
+            FlowtimeBreaks::parse_table("50 = 8, 25=5,"),
+            Some(vec![(25, 5), (50, 8)])
+        );
+        for text in ["", "25", "25=x", "25=5, 50"] {
+            assert_eq!(FlowtimeBreaks::parse_table(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn only_short_breaks_by_default() {
+        use WorkTimes::*;
+        assert_eq!(
+            phases(TimerData::default().long_break_every, 6),
+            vec![Short, Work, Short, Work, Short, Work]
+        );
+    }
+
+    #[test]
+    fn long_break_after_every_n_work_phases() {
+        use WorkTimes::*;
+        assert_eq!(
+            phases(2, 8),
+            vec![Short, Work, Long, Work, Short, Work, Long, Work]
+        );
+        assert_eq!(phases(1, 4), vec![Long, Work, Long, Work]);
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(TimerData::dur_as_minutes(&Duration::from_secs(65)), " 1:05");
+        assert_eq!(
+            TimerData::dur_as_minutes(&Duration::from_secs(1500)),
+            "25:00"
+        );
     }
 }
-#[derive(Debug, Clone)]
-pub struct AlertPlayingError;
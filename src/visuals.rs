@@ -1,20 +1,99 @@
+use crate::timer::WorkTimes;
 use eframe::egui::{Color32, FontFamily, FontId, Vec2, Visuals};
 use egui::style::Spacing;
 use egui::style::{WidgetVisuals, Widgets};
 use egui::Margin;
 use egui::Stroke;
 use egui::TextStyle;
+use std::collections::HashMap;
+
+//How long (in seconds) the fade between two phase color schemes takes.
+const PHASE_TRANSITION_TIME: f64 = 0.6;
 
 pub struct TimerAppVisuals {
-    color_scheme: AppColorScheme,
+    color_scheme: PhaseColorScheme,
+    current_phase: WorkTimes,
+    transition: Option<PhaseTransition>,
+}
+
+//A running fade from one phase color scheme to another, started at egui time `start_time`.
+struct PhaseTransition {
+    from: PhaseColorScheme,
+    start_time: f64,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
-#[serde(default)]
+#[serde(from = "SavedColorScheme")]
 pub struct AppColorScheme {
+    pub phase_colors: HashMap<WorkTimes, PhaseColorScheme>,
+}
+
+//Saved colors. Before there were colors per phase a single scheme was saved, its colors become
+//the work colors.
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct SavedColorScheme {
+    phase_colors: HashMap<WorkTimes, PhaseColorScheme>,
+    fill_color: Color32,
+    timer_paused: Color32,
+    timer_active: Color32,
+    ligth_bg_color: Color32,
+    dark_bg_color: Color32,
+    ligth_bg_stroke: Color32,
+    ligth_fg_stroke: Color32,
+    dark_bg_stroke: Color32,
+    dark_fg_stroke: Color32,
+}
+
+impl Default for SavedColorScheme {
+    fn default() -> Self {
+        let work = PhaseColorScheme::default();
+        SavedColorScheme {
+            phase_colors: HashMap::new(),
+            fill_color: work.fill_color,
+            timer_paused: work.timer_paused,
+            timer_active: work.timer_active,
+            ligth_bg_color: work.ligth_bg_color,
+            dark_bg_color: work.dark_bg_color,
+            ligth_bg_stroke: work.ligth_bg_stroke,
+            ligth_fg_stroke: work.ligth_fg_stroke,
+            dark_bg_stroke: work.dark_bg_stroke,
+            dark_fg_stroke: work.dark_fg_stroke,
+        }
+    }
+}
+
+impl From<SavedColorScheme> for AppColorScheme {
+    fn from(saved: SavedColorScheme) -> Self {
+        let mut scheme = AppColorScheme::default();
+        if saved.phase_colors.is_empty() {
+            let work = PhaseColorScheme {
+                fill_color: saved.fill_color,
+                timer_paused: saved.timer_paused,
+                timer_active: saved.timer_active,
+                ligth_bg_color: saved.ligth_bg_color,
+                dark_bg_color: saved.dark_bg_color,
+                ligth_bg_stroke: saved.ligth_bg_stroke,
+                ligth_fg_stroke: saved.ligth_fg_stroke,
+                dark_bg_stroke: saved.dark_bg_stroke,
+                dark_fg_stroke: saved.dark_fg_stroke,
+                ..PhaseColorScheme::default()
+            };
+            scheme.phase_colors.insert(WorkTimes::Work, work);
+        } else {
+            scheme.phase_colors.extend(saved.phase_colors);
+        }
+        scheme
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PhaseColorScheme {
     pub fill_color: Color32,
     pub timer_paused: Color32,
     pub timer_active: Color32,
+    pub timer_overtime: Color32,
     pub ligth_bg_color: Color32,
     pub dark_bg_color: Color32,
     pub ligth_bg_stroke: Color32,
@@ -26,9 +105,60 @@ pub struct AppColorScheme {
 impl Default for AppColorScheme {
     fn default() -> Self {
         AppColorScheme {
+            phase_colors: HashMap::from([
+                (WorkTimes::Work, PhaseColorScheme::default()),
+                (
+                    WorkTimes::Short,
+                    PhaseColorScheme {
+                        fill_color: Color32::from_rgb(22, 66, 70),
+                        timer_active: Color32::from_rgb(16, 48, 52),
+                        timer_paused: Color32::from_rgb(10, 30, 33),
+                        timer_overtime: Color32::from_rgb(90, 24, 40),
+                        ligth_bg_color: Color32::from_rgb(56, 133, 138),
+                        dark_bg_color: Color32::from_rgb(72, 160, 160),
+                        ligth_fg_stroke: Color32::from_rgb(224, 245, 243),
+                        ligth_bg_stroke: Color32::from_rgb(120, 190, 185),
+                        dark_fg_stroke: Color32::from_rgb(224, 245, 243),
+                        dark_bg_stroke: Color32::from_rgb(150, 215, 205),
+                    },
+                ),
+                (
+                    WorkTimes::Long,
+                    PhaseColorScheme {
+                        fill_color: Color32::from_rgb(24, 48, 72),
+                        timer_active: Color32::from_rgb(20, 30, 70),
+                        timer_paused: Color32::from_rgb(12, 18, 42),
+                        timer_overtime: Color32::from_rgb(84, 22, 52),
+                        ligth_bg_color: Color32::from_rgb(57, 112, 151),
+                        dark_bg_color: Color32::from_rgb(80, 140, 180),
+                        ligth_fg_stroke: Color32::from_rgb(225, 236, 247),
+                        ligth_bg_stroke: Color32::from_rgb(130, 170, 210),
+                        dark_fg_stroke: Color32::from_rgb(225, 236, 247),
+                        dark_bg_stroke: Color32::from_rgb(160, 195, 230),
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+impl AppColorScheme {
+    //Falls back on the default work colors if a phase is missing from (older) saved settings.
+    pub fn phase(&self, work_time: &WorkTimes) -> PhaseColorScheme {
+        self.phase_colors
+            .get(work_time)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for PhaseColorScheme {
+    fn default() -> Self {
+        PhaseColorScheme {
             fill_color: Color32::from_rgb(88, 31, 24),
             timer_active: Color32::from_rgb(33, 44, 91),
             timer_paused: Color32::from_rgb(16, 22, 45),
+            timer_overtime: Color32::from_rgb(120, 16, 16),
             ligth_bg_color: Color32::from_rgb(217, 93, 57),
             dark_bg_color: Color32::from_rgb(241, 136, 5),
             ligth_fg_stroke: Color32::from_rgb(249, 224, 217),
@@ -39,20 +169,104 @@ impl Default for AppColorScheme {
     }
 }
 
+impl PhaseColorScheme {
+    //Blends every color of the scheme, t = 0.0 gives self and t = 1.0 gives other.
+    fn lerp(&self, other: &PhaseColorScheme, t: f32) -> PhaseColorScheme {
+        let mix = |a: Color32, b: Color32| {
+            let channel = |x: u8, y: u8| egui::emath::lerp(x as f32..=y as f32, t).round() as u8;
+            Color32::from_rgba_unmultiplied(
+                channel(a.r(), b.r()),
+                channel(a.g(), b.g()),
+                channel(a.b(), b.b()),
+                channel(a.a(), b.a()),
+            )
+        };
+        PhaseColorScheme {
+            fill_color: mix(self.fill_color, other.fill_color),
+            timer_paused: mix(self.timer_paused, other.timer_paused),
+            timer_active: mix(self.timer_active, other.timer_active),
+            timer_overtime: mix(self.timer_overtime, other.timer_overtime),
+            ligth_bg_color: mix(self.ligth_bg_color, other.ligth_bg_color),
+            dark_bg_color: mix(self.dark_bg_color, other.dark_bg_color),
+            ligth_bg_stroke: mix(self.ligth_bg_stroke, other.ligth_bg_stroke),
+            ligth_fg_stroke: mix(self.ligth_fg_stroke, other.ligth_fg_stroke),
+            dark_bg_stroke: mix(self.dark_bg_stroke, other.dark_bg_stroke),
+            dark_fg_stroke: mix(self.dark_fg_stroke, other.dark_fg_stroke),
+        }
+    }
+}
+
 impl Default for TimerAppVisuals {
     fn default() -> Self {
         TimerAppVisuals {
-            color_scheme: AppColorScheme::default(),
+            color_scheme: PhaseColorScheme::default(),
+            current_phase: WorkTimes::Work,
+            transition: None,
         }
     }
 }
 
 impl TimerAppVisuals {
-    pub fn setup_app_visuals(&mut self, cc: &eframe::CreationContext<'_>) {
-        cc.egui_ctx.set_pixels_per_point(2.5);
+    pub fn setup_app_visuals(
+        &mut self,
+        cc: &eframe::CreationContext<'_>,
+        app_color_scheme: &AppColorScheme,
+        work_time: &WorkTimes,
+        ui_scale: f32,
+    ) {
+        TimerAppVisuals::set_ui_scale(&cc.egui_ctx, ui_scale);
         TimerAppVisuals::setup_fonts(cc);
         TimerAppVisuals::setup_style(cc);
-        self.setup_visuals(cc);
+        self.current_phase = *work_time;
+        self.color_scheme = app_color_scheme.phase(work_time);
+        self.setup_visuals(&cc.egui_ctx);
+    }
+
+    pub fn set_ui_scale(ctx: &egui::Context, ui_scale: f32) {
+        if ctx.pixels_per_point() != ui_scale {
+            ctx.set_pixels_per_point(ui_scale);
+        }
+    }
+
+    //The colors currently on screen, these are in between two phases during a transition.
+    pub fn current_colors(&self) -> &PhaseColorScheme {
+        &self.color_scheme
+    }
+
+    //Called every frame, starts a fade to the colors of the new phase when the phase changes and
+    //re-applies the egui visuals until the fade is done.
+    pub fn update_phase_visuals(
+        &mut self,
+        ctx: &egui::Context,
+        app_color_scheme: &AppColorScheme,
+        work_time: &WorkTimes,
+    ) {
+        let now = ctx.input(|i| i.time);
+        if *work_time != self.current_phase {
+            self.current_phase = *work_time;
+            self.transition = Some(PhaseTransition {
+                from: self.color_scheme.clone(),
+                start_time: now,
+            });
+        }
+        let target = app_color_scheme.phase(&self.current_phase);
+        let new_colors = match &self.transition {
+            Some(transition) => {
+                let t = ((now - transition.start_time) / PHASE_TRANSITION_TIME).clamp(0.0, 1.0);
+                let colors = transition.from.lerp(&target, t as f32);
+                if t >= 1.0 {
+                    self.transition = None;
+                } else {
+                    ctx.request_repaint();
+                }
+                colors
+            }
+            None => target,
+        };
+        if new_colors != self.color_scheme {
+            self.color_scheme = new_colors;
+            self.setup_visuals(ctx);
+        }
     }
     fn setup_fonts(cc: &eframe::CreationContext<'_>) {
         let mut fonts = egui::FontDefinitions::default();
@@ -68,8 +282,8 @@ impl TimerAppVisuals {
             .insert(0, "Roboto".to_owned());
         cc.egui_ctx.set_fonts(fonts);
     }
-    fn setup_visuals(&self, cc: &eframe::CreationContext<'_>) {
-        cc.egui_ctx.set_visuals(Visuals {
+    fn setup_visuals(&self, ctx: &egui::Context) {
+        ctx.set_visuals(Visuals {
             panel_fill: self.color_scheme.fill_color,
             window_fill: self.color_scheme.fill_color,
             selection: egui::style::Selection {
@@ -160,3 +374,38 @@ impl TimerAppVisuals {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_the_single_saved_scheme() {
+        let saved =
+            "(fill_color:((1,2,3,255)),timer_paused:((4,5,6,255)),dark_fg_stroke:((7,8,9,255)))";
+        let scheme: AppColorScheme = ron::from_str(saved).unwrap();
+        let work = scheme.phase(&WorkTimes::Work);
+        assert_eq!(work.fill_color, Color32::from_rgb(1, 2, 3));
+        assert_eq!(work.timer_paused, Color32::from_rgb(4, 5, 6));
+        assert_eq!(work.dark_fg_stroke, Color32::from_rgb(7, 8, 9));
+        assert_eq!(work.timer_active, PhaseColorScheme::default().timer_active);
+        let default = AppColorScheme::default();
+        assert!(scheme.phase(&WorkTimes::Short) == default.phase(&WorkTimes::Short));
+    }
+
+    #[test]
+    fn round_trips_phase_colors() {
+        let mut scheme = AppColorScheme::default();
+        scheme
+            .phase_colors
+            .get_mut(&WorkTimes::Long)
+            .unwrap()
+            .fill_color = Color32::from_rgb(10, 20, 30);
+        let saved = ron::to_string(&scheme).unwrap();
+        let loaded: AppColorScheme = ron::from_str(&saved).unwrap();
+        assert!(loaded.phase_colors == scheme.phase_colors);
+
+        let empty: AppColorScheme = ron::from_str("()").unwrap();
+        assert!(empty.phase_colors == AppColorScheme::default().phase_colors);
+    }
+}